@@ -0,0 +1,30 @@
+// Copyright 2019 Steve Degosserie
+// Runtime APIs exposing Grid state to the node, e.g. for RPC
+
+use crate::AccountId;
+use crate::grid_pike::{Agent, OrgId, Organization, Permission, Role};
+use crate::grid_schema::{Name, PropertyDefinition, PropertyValue, Schema};
+use client::decl_runtime_apis;
+use rstd::prelude::*;
+
+decl_runtime_apis! {
+	/// Read-only access to Pike organizations, agents & roles.
+	pub trait GridPikeApi {
+		fn org(id: OrgId) -> Option<Organization>;
+		fn agent(account: AccountId, org_id: OrgId) -> Option<Agent<AccountId>>;
+		fn agents_of_account(account: AccountId) -> Vec<Agent<AccountId>>;
+		fn agents_of_org(id: OrgId) -> Vec<Agent<AccountId>>;
+		fn roles_of_agent(account: AccountId, org_id: OrgId) -> Vec<Role>;
+		fn is_admin(account: AccountId, org_id: OrgId) -> bool;
+		fn has_permission(account: AccountId, org_id: OrgId, permission: Permission) -> bool;
+	}
+
+	/// Read-only access to schemas, and dry-run validation of property values.
+	pub trait GridSchemaApi {
+		/// Returns a schema along with the property definitions of its current version.
+		fn schema(name: Name) -> Option<(Schema, Vec<PropertyDefinition>)>;
+		fn schemas_of_org(org_id: OrgId) -> Vec<Schema>;
+		/// Validates values as product / location creation would, returning the error message.
+		fn validate_values(schema: Name, values: Vec<PropertyValue>) -> rstd::result::Result<(), Vec<u8>>;
+	}
+}
@@ -0,0 +1,299 @@
+// Copyright 2019 Steve Degosserie
+// IPFS content identifiers (CID) parsing, see https://github.com/multiformats/cid
+
+use rstd::prelude::*;
+use support::ensure;
+
+pub const ERR_CID_REQUIRED: &str = "CID required";
+pub const ERR_CID_MULTIBASE_UNSUPPORTED: &str = "CID multibase not supported, expected base32 (b), base58btc (z) or base16 (f)";
+pub const ERR_CID_INVALID_ENCODING: &str = "CID is not correctly encoded for its multibase";
+pub const ERR_CID_VERSION_UNSUPPORTED: &str = "CID version not supported";
+pub const ERR_CID_INVALID_VARINT: &str = "CID contains a malformed varint";
+pub const ERR_CID_DIGEST_LENGTH_MISMATCH: &str = "CID multihash digest length mismatch";
+
+// Multicodec codes
+pub const CODEC_RAW: u64 = 0x55;
+pub const CODEC_DAG_PB: u64 = 0x70;
+pub const HASH_SHA2_256: u64 = 0x12;
+const HASH_SHA2_512: u64 = 0x13;
+const HASH_KECCAK_256: u64 = 0x1b;
+const HASH_BLAKE2B_256: u64 = 0xb220;
+
+// CIDv0 are bare sha2-256 multihashes, always 46 characters long in base58btc
+const CID_V0_LENGTH: usize = 46;
+const CID_V0_PREFIX: &[u8] = b"Qm";
+// Multiformats varints are at most 9 bytes long
+const VARINT_LIMIT: usize = 9;
+
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+type Result<T> = rstd::result::Result<T, &'static str>;
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, PartialEq, Eq)]
+pub struct Cid {
+	pub version: u8,
+	// Multicodec of the content, e.g. raw or dag-pb
+	pub codec: u64,
+	// Multicodec of the hash function
+	pub hash_code: u64,
+	pub digest: Vec<u8>,
+}
+
+impl Cid {
+	/// CIDv1 of raw content, given its sha2-256 digest.
+	pub fn raw_sha2_256(digest: [u8; 32]) -> Self {
+		Cid { version: 1, codec: CODEC_RAW, hash_code: HASH_SHA2_256, digest: digest.to_vec() }
+	}
+
+	/// Binary form of a CIDv1, i.e. version, codec & multihash.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = vec!();
+		write_varint(&mut bytes, self.version as u64);
+		write_varint(&mut bytes, self.codec);
+		write_varint(&mut bytes, self.hash_code);
+		write_varint(&mut bytes, self.digest.len() as u64);
+		bytes.extend_from_slice(&self.digest);
+		bytes
+	}
+
+	/// Base32 string form of a CIDv1, as used by IPFS (e.g. `bafk...`).
+	pub fn to_base32(&self) -> Vec<u8> {
+		let mut encoded = vec![b'b'];
+		encoded.extend(encode_base32(&self.to_bytes()));
+		encoded
+	}
+}
+
+/// Parses a CIDv0 (`Qm...`), or a CIDv1 in base32, base58btc or base16.
+pub fn parse_cid(cid: &[u8]) -> Result<Cid> {
+	ensure!(!cid.is_empty(), ERR_CID_REQUIRED);
+
+	if cid.len() == CID_V0_LENGTH && cid.starts_with(CID_V0_PREFIX) {
+		// The prefix implies a sha2-256 multihash, whose length is checked
+		let (hash_code, digest) = parse_multihash(&decode_base58btc(cid)?)?;
+		return Ok(Cid { version: 0, codec: CODEC_DAG_PB, hash_code, digest });
+	}
+
+	let bytes = match cid[0] {
+		b'b' => decode_base32(&cid[1..])?,
+		b'z' => decode_base58btc(&cid[1..])?,
+		b'f' => decode_base16(&cid[1..])?,
+		_ => return Err(ERR_CID_MULTIBASE_UNSUPPORTED)
+	};
+	let mut input = &bytes[..];
+	let version = read_varint(&mut input)?;
+	ensure!(version == 1, ERR_CID_VERSION_UNSUPPORTED);
+	let codec = read_varint(&mut input)?;
+	let (hash_code, digest) = parse_multihash(input)?;
+	Ok(Cid { version: 1, codec, hash_code, digest })
+}
+
+fn parse_multihash(mut input: &[u8]) -> Result<(u64, Vec<u8>)> {
+	let hash_code = read_varint(&mut input)?;
+	let length = read_varint(&mut input)?;
+	ensure!(input.len() as u64 == length, ERR_CID_DIGEST_LENGTH_MISMATCH);
+	if let Some(expected) = digest_length(hash_code) {
+		ensure!(length == expected, ERR_CID_DIGEST_LENGTH_MISMATCH);
+	}
+	Ok((hash_code, input.to_vec()))
+}
+
+// Digest length of the common hash functions, others are taken as declared
+fn digest_length(hash_code: u64) -> Option<u64> {
+	match hash_code {
+		HASH_SHA2_256 | HASH_KECCAK_256 | HASH_BLAKE2B_256 => Some(32),
+		HASH_SHA2_512 => Some(64),
+		_ => None
+	}
+}
+
+// Unsigned LEB128, as specified by https://github.com/multiformats/unsigned-varint
+fn read_varint(input: &mut &[u8]) -> Result<u64> {
+	let bytes: &[u8] = *input;
+	let mut value: u64 = 0;
+	for (i, byte) in bytes.iter().enumerate().take(VARINT_LIMIT) {
+		value |= ((byte & 0x7f) as u64) << (7 * i);
+		if byte & 0x80 == 0 {
+			// Trailing zero bytes would make several encodings of the same value
+			ensure!(i == 0 || *byte != 0, ERR_CID_INVALID_VARINT);
+			*input = &bytes[i + 1..];
+			return Ok(value);
+		}
+	}
+	Err(ERR_CID_INVALID_VARINT)
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+	while value >= 0x80 {
+		output.push((value as u8 & 0x7f) | 0x80);
+		value >>= 7;
+	}
+	output.push(value as u8);
+}
+
+// RFC 4648 lowercase base32, without padding
+fn decode_base32(input: &[u8]) -> Result<Vec<u8>> {
+	let mut bytes = Vec::with_capacity(input.len() * 5 / 8);
+	let mut buffer: u32 = 0;
+	let mut bits = 0;
+	for c in input {
+		let digit = BASE32_ALPHABET.iter().position(|a| a == c).ok_or(ERR_CID_INVALID_ENCODING)?;
+		buffer = (buffer << 5) | digit as u32;
+		bits += 5;
+		if bits >= 8 {
+			bits -= 8;
+			bytes.push((buffer >> bits) as u8);
+			buffer &= (1 << bits) - 1;
+		}
+	}
+	// Leftover bits are padding, which must be zero and shorter than a digit
+	ensure!(bits < 5 && buffer == 0, ERR_CID_INVALID_ENCODING);
+	Ok(bytes)
+}
+
+fn encode_base32(input: &[u8]) -> Vec<u8> {
+	let mut encoded = Vec::with_capacity((input.len() * 8 + 4) / 5);
+	let mut buffer: u32 = 0;
+	let mut bits = 0;
+	for byte in input {
+		buffer = (buffer << 8) | *byte as u32;
+		bits += 8;
+		while bits >= 5 {
+			bits -= 5;
+			encoded.push(BASE32_ALPHABET[(buffer >> bits) as usize & 0x1f]);
+		}
+		buffer &= (1 << bits) - 1;
+	}
+	if bits > 0 {
+		encoded.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 0x1f]);
+	}
+	encoded
+}
+
+fn decode_base58btc(input: &[u8]) -> Result<Vec<u8>> {
+	// Big-endian base256 digits, built up one base58 digit at a time
+	let mut bytes: Vec<u8> = vec!();
+	for c in input {
+		let mut carry = BASE58_ALPHABET.iter().position(|a| a == c).ok_or(ERR_CID_INVALID_ENCODING)? as u32;
+		for byte in bytes.iter_mut().rev() {
+			carry += *byte as u32 * 58;
+			*byte = carry as u8;
+			carry >>= 8;
+		}
+		while carry > 0 {
+			bytes.insert(0, carry as u8);
+			carry >>= 8;
+		}
+	}
+	// Leading '1's stand for leading zero bytes
+	let zeros = input.iter().take_while(|c| **c == BASE58_ALPHABET[0]).count();
+	let mut decoded = vec![0; zeros];
+	decoded.extend(bytes);
+	Ok(decoded)
+}
+
+fn decode_base16(input: &[u8]) -> Result<Vec<u8>> {
+	ensure!(input.len() % 2 == 0, ERR_CID_INVALID_ENCODING);
+	let mut bytes = Vec::with_capacity(input.len() / 2);
+	for pair in input.chunks(2) {
+		bytes.push((hex_digit(pair[0])? << 4) | hex_digit(pair[1])?);
+	}
+	Ok(bytes)
+}
+
+fn hex_digit(c: u8) -> Result<u8> {
+	match c {
+		b'0'..=b'9' => Ok(c - b'0'),
+		b'a'..=b'f' => Ok(c - b'a' + 10),
+		_ => Err(ERR_CID_INVALID_ENCODING)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// sha2-256 of "hello world"
+	const HELLO_DIGEST: &str = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+	const HELLO_CID_V1: &[u8] = b"bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+	// sha2-256 of the empty string
+	const EMPTY_DIGEST: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+	const EMPTY_CID_V0: &[u8] = b"QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n";
+	const EMPTY_CID_V1: &[u8] = b"bafybeihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku";
+
+	fn digest(hex: &str) -> Vec<u8> {
+		decode_base16(hex.as_bytes()).unwrap()
+	}
+
+	fn hello_cid() -> Cid {
+		Cid { version: 1, codec: CODEC_RAW, hash_code: HASH_SHA2_256, digest: digest(HELLO_DIGEST) }
+	}
+
+	#[test]
+	fn parse_cid_v0() {
+		assert_eq!(
+			parse_cid(EMPTY_CID_V0),
+			Ok(Cid { version: 0, codec: CODEC_DAG_PB, hash_code: HASH_SHA2_256, digest: digest(EMPTY_DIGEST) })
+		);
+	}
+
+	#[test]
+	fn parse_cid_v1_with_supported_multibases() {
+		assert_eq!(parse_cid(HELLO_CID_V1), Ok(hello_cid()));
+		assert_eq!(parse_cid(b"zb2rhj7crUKTQYRGCRATFaQ6YFLTde2YzdqbbhAASkL9uRDXn"), Ok(hello_cid()));
+		assert_eq!(
+			parse_cid(b"f01551220b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"),
+			Ok(hello_cid())
+		);
+		assert_eq!(
+			parse_cid(EMPTY_CID_V1),
+			Ok(Cid { version: 1, codec: CODEC_DAG_PB, hash_code: HASH_SHA2_256, digest: digest(EMPTY_DIGEST) })
+		);
+	}
+
+	#[test]
+	fn cid_v1_to_base32() {
+		assert_eq!(hello_cid().to_base32(), HELLO_CID_V1.to_vec());
+
+		let mut hello_digest = [0u8; 32];
+		hello_digest.copy_from_slice(&digest(HELLO_DIGEST));
+		assert_eq!(Cid::raw_sha2_256(hello_digest), hello_cid());
+	}
+
+	#[test]
+	fn parse_cid_with_invalid_args() {
+		assert_eq!(parse_cid(b""), Err(ERR_CID_REQUIRED));
+		assert_eq!(parse_cid(b"mAVUSILlNJ7mTTT4IpS5S19p9q/rEhO/jelOA7pCI96zi783p"), Err(ERR_CID_MULTIBASE_UNSUPPORTED));
+		// Uppercase & digits outside of the base32 alphabet
+		assert_eq!(parse_cid(b"bAFKREIFZJUT3TE2NHYEKKLSS27NH3K72YSCO7Y32KOAO5EEI66WOF36N5E"), Err(ERR_CID_INVALID_ENCODING));
+		assert_eq!(parse_cid(b"bafkrei0"), Err(ERR_CID_INVALID_ENCODING));
+		assert_eq!(parse_cid(b"f0155122"), Err(ERR_CID_INVALID_ENCODING));
+		// Base58btc has no 0, O, I or l
+		assert_eq!(parse_cid(b"QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR10"), Err(ERR_CID_INVALID_ENCODING));
+	}
+
+	#[test]
+	fn parse_cid_with_invalid_content() {
+		// Version 2
+		assert_eq!(parse_cid(b"f02551220b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"), Err(ERR_CID_VERSION_UNSUPPORTED));
+		// Unterminated codec varint
+		assert_eq!(parse_cid(b"f01ff"), Err(ERR_CID_INVALID_VARINT));
+		// Non-minimal codec varint
+		assert_eq!(parse_cid(b"f01d500"), Err(ERR_CID_INVALID_VARINT));
+		// Truncated digest
+		assert_eq!(parse_cid(b"f01551220b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcd"), Err(ERR_CID_DIGEST_LENGTH_MISMATCH));
+		// sha2-256 digest declared 16 bytes long
+		assert_eq!(parse_cid(b"f01551210b94d27b9934d3e08a52e52d7da7dabfa"), Err(ERR_CID_DIGEST_LENGTH_MISMATCH));
+	}
+
+	#[test]
+	fn parse_cid_v0_with_invalid_digest_length() {
+		// sha2-256 multihash declaring a 33 bytes digest
+		assert_eq!(
+			parse_cid(b"QmvtmxgcvzCAE9Xe1xTuBMgzERogQ9zoo2uhm1AbrnWNG4"),
+			Err(ERR_CID_DIGEST_LENGTH_MISMATCH)
+		);
+	}
+}
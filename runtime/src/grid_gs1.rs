@@ -0,0 +1,119 @@
+// Copyright 2019 Steve Degosserie
+// GS1 identification keys and master data (products, locations), shared by the Grid runtime modules
+
+use crate::grid_pike::OrgId;
+use crate::grid_pike::Module as PikeModule;
+use crate::grid_schema::PropertyValue;
+use crate::grid_schema::Trait as SchemaTrait;
+use crate::grid_schema::Module as SchemaModule;
+use support::{ensure, dispatch::Result};
+
+/// Checks the trailing check digit of a GS1 identification key (GTIN, GLN, ...).
+pub fn has_valid_gs1_check_digit(id: &[u8]) -> bool {
+	if id.len() < 2 || !id.iter().all(|d| d.is_ascii_digit()) {
+		return false;
+	}
+	let (digits, check_digit) = id.split_at(id.len() - 1);
+	// Weights alternate 3 & 1, starting from the rightmost digit
+	let sum: u32 = digits.iter().rev().enumerate()
+		.map(|(i, d)| u32::from(d - b'0') * if i % 2 == 0 { 3 } else { 1 })
+		.sum();
+	(10 - sum % 10) % 10 == u32::from(check_digit[0] - b'0')
+}
+
+/// Checks a GS1 identification key of one of the given lengths.
+pub fn validate_gs1_key(
+	id: &[u8], lengths: &[usize],
+	err_required: &'static str, err_invalid: &'static str) -> Result {
+	ensure!(id.len() > 0, err_required);
+	ensure!(lengths.contains(&id.len()), err_invalid);
+	ensure!(has_valid_gs1_check_digit(id), err_invalid);
+	Ok(())
+}
+
+/// Checks that the sender may create master data for an organization,
+/// with properties valid against the current version of its schema.
+pub fn ensure_can_create<T: SchemaTrait>(
+	sender: &T::AccountId, owner: &OrgId, permission: &[u8],
+	schema: &[u8], properties: &[PropertyValue]) -> Result {
+	// Validate org exists
+	<PikeModule<T>>::validate_existing_org(owner)?;
+	// Validate signer is an active agent of the specified org,
+	// with the right to create master data
+	<PikeModule<T>>::ensure_permission(sender, owner.clone(), permission)?;
+	<SchemaModule<T>>::validate_new_values(schema, properties)
+}
+
+/// Same as `ensure_can_create`, for master data already stored.
+pub fn ensure_can_update<T: SchemaTrait>(
+	sender: &T::AccountId, owner: &OrgId, permission: &[u8],
+	schema: &[u8], properties: &[PropertyValue]) -> Result {
+	// Validate signer is an active agent of the owner org,
+	// with the right to update master data
+	<PikeModule<T>>::ensure_permission(sender, owner.clone(), permission)?;
+	<SchemaModule<T>>::validate_values(schema, properties)
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+	use crate::grid_pike::tests::{
+		store_test_org, store_test_agent, store_admin_role, store_test_role};
+	use crate::grid_schema::PropertyDefinition;
+	use crate::grid_schema::tests::store_test_schema_with_properties;
+
+	pub const TEST_ORG_ID: &str = "did:example:123456789abcdefghijk";
+	pub const TEST_ORG_NAME: &str = "Parity Tech";
+	pub const TEST_EXISTING_ORG: &str = "did:example:azertyuiop";
+
+	pub fn org() -> OrgId {
+		String::from(TEST_ORG_ID).into_bytes()
+	}
+
+	/// Stores the test organization with an admin agent, and the schema of its master data.
+	pub fn store_test_admin(admin: u64, schema: &[u8], definitions: Vec<PropertyDefinition>) -> OrgId {
+		let owner = org();
+		let admin_role_id = store_admin_role(owner.clone());
+		store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+		store_test_agent(admin, owner.clone(), true, vec![admin_role_id]);
+		store_test_schema_with_properties(schema.to_vec(), owner.clone(), definitions);
+		owner
+	}
+
+	/// Stores the test organization with an agent holding a single permission.
+	pub fn store_test_editor(agent: u64, permission: &[u8]) -> OrgId {
+		let owner = org();
+		let role_id = store_test_role(owner.clone(), b"editor".to_vec(), vec![permission.to_vec()]);
+		store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+		store_test_agent(agent, owner.clone(), true, vec![role_id]);
+		owner
+	}
+
+	/// Stores another organization with an admin agent.
+	pub fn store_other_admin(admin: u64) {
+		let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+		let admin_role_id = store_admin_role(other_org.clone());
+		store_test_org(other_org.clone(), String::from(TEST_ORG_NAME).into_bytes());
+		store_test_agent(admin, other_org, true, vec![admin_role_id]);
+	}
+
+	#[test]
+	fn gs1_check_digit_validation() {
+		assert!(has_valid_gs1_check_digit(b"96385074"));
+		assert!(has_valid_gs1_check_digit(b"036000291452"));
+		assert!(has_valid_gs1_check_digit(b"4006381333931"));
+		assert!(has_valid_gs1_check_digit(b"10614141000415"));
+		assert!(!has_valid_gs1_check_digit(b"4006381333932"));
+		assert!(!has_valid_gs1_check_digit(b"40063813339a1"));
+	}
+
+	#[test]
+	fn gs1_key_validation() {
+		let (required, invalid) = ("required", "invalid");
+
+		assert_eq!(validate_gs1_key(b"4006381333931", &[13], required, invalid), Ok(()));
+		assert_eq!(validate_gs1_key(b"", &[13], required, invalid), Err(required));
+		assert_eq!(validate_gs1_key(b"4006381333931", &[8, 12], required, invalid), Err(invalid));
+		assert_eq!(validate_gs1_key(b"4006381333932", &[13], required, invalid), Err(invalid));
+	}
+}
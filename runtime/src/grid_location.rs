@@ -0,0 +1,515 @@
+// Copyright 2019 Steve Degosserie
+// Hyperledger Grid Location compatible runtime module
+
+use crate::grid_gs1::{validate_gs1_key, ensure_can_create, ensure_can_update};
+use crate::grid_pike::{OrgId, validate_org_id};
+use crate::grid_pike::Module as PikeModule;
+use crate::grid_schema::PropertyValue;
+use crate::grid_schema::Trait as SchemaTrait;
+use crate::grid_schema::Module as SchemaModule;
+use rstd::prelude::*;
+use parity_codec::{Decode, Encode};
+use support::{
+	decl_module, decl_storage, decl_event,
+	ensure, StorageMap,
+	dispatch::Result
+};
+use system::ensure_signed;
+
+pub const ERR_LOCATION_ID_REQUIRED: &str = "Location ID required";
+pub const ERR_LOCATION_ID_INVALID_GLN: &str = "Location ID must be a valid GS1 GLN-13";
+pub const ERR_LOCATION_ALREADY_EXISTS: &str = "Location already exists";
+pub const ERR_LOCATION_DOES_NOT_EXIST: &str = "Location does not exist";
+
+const GLN_LENGTHS: [usize; 1] = [13];
+
+pub const PERM_CAN_CREATE_LOCATION: &[u8] = b"location::can-create-location";
+pub const PERM_CAN_UPDATE_LOCATION: &[u8] = b"location::can-update-location";
+pub const PERM_CAN_DELETE_LOCATION: &[u8] = b"location::can-delete-location";
+
+// Schema that location properties are validated against
+pub const LOCATION_SCHEMA: &[u8] = b"gs1_location";
+
+pub type LocationId = Vec<u8>;
+
+pub fn validate_gln(id: &[u8]) -> Result {
+	validate_gs1_key(id, &GLN_LENGTHS, ERR_LOCATION_ID_REQUIRED, ERR_LOCATION_ID_INVALID_GLN)
+}
+
+pub fn validate_location_id(location_namespace: &LocationNamespace, location_id: &[u8]) -> Result {
+	match location_namespace {
+		LocationNamespace::GS1 => validate_gln(location_id),
+	}
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum LocationNamespace {
+	GS1,
+}
+
+impl Default for LocationNamespace {
+	fn default() -> Self {
+		LocationNamespace::GS1
+	}
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct Location {
+	pub location_id: LocationId,
+	pub location_namespace: LocationNamespace,
+	// Index of the owner organization
+	pub owner: u32,
+	pub properties: Vec<PropertyValue>,
+}
+
+#[derive(Default)]
+pub struct LocationBuilder {
+	location_id: LocationId,
+	location_namespace: LocationNamespace,
+	owner: u32,
+	properties: Vec<PropertyValue>,
+}
+impl LocationBuilder {
+	pub fn with_id(mut self, location_id: LocationId) -> Self {
+		self.location_id = location_id;
+		self
+	}
+
+	pub fn with_namespace(mut self, location_namespace: LocationNamespace) -> Self {
+		self.location_namespace = location_namespace;
+		self
+	}
+
+	pub fn with_owner(mut self, owner: u32) -> Self {
+		self.owner = owner;
+		self
+	}
+
+	pub fn with_properties(mut self, properties: Vec<PropertyValue>) -> Self {
+		self.properties = properties;
+		self
+	}
+
+	pub fn build(self) -> rstd::result::Result<Location, &'static str> {
+		validate_location_id(&self.location_namespace, &self.location_id)?;
+		let mut location = Location::default();
+		location.location_id = self.location_id;
+		location.location_namespace = self.location_namespace;
+		location.owner = self.owner;
+		location.properties = self.properties;
+		Ok(location)
+	}
+}
+
+pub trait Trait: SchemaTrait {
+	type Event: From<Event> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as GridLocation {
+		Locations get(location_by_id): map LocationId => Option<Location>;
+	}
+}
+
+decl_event!(
+	pub enum Event {
+		LocationCreated(LocationId, OrgId),
+		LocationUpdated(LocationId, OrgId),
+		LocationDeleted(LocationId, OrgId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		pub fn create_location(
+			origin, location_id: LocationId, location_namespace: LocationNamespace,
+			owner: OrgId, properties: Vec<PropertyValue>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			validate_location_id(&location_namespace, &location_id)?;
+			validate_org_id(&owner)?;
+			Self::validate_new_location(&location_id)?;
+			ensure_can_create::<T>(&sender, &owner, PERM_CAN_CREATE_LOCATION, LOCATION_SCHEMA, &properties)?;
+
+			let location = LocationBuilder::default()
+				.with_id(location_id.clone())
+				.with_namespace(location_namespace)
+				.with_owner(<PikeModule<T>>::index_org(&owner)?)
+				.with_properties(properties)
+				.build()?;
+
+			<Locations<T>>::insert(&location_id, location);
+			<SchemaModule<T>>::warn_if_deprecated(LOCATION_SCHEMA);
+
+			Self::deposit_event(Event::LocationCreated(location_id, owner));
+
+			Ok(())
+		}
+
+		pub fn update_location(
+			origin, location_id: LocationId,
+			properties: Vec<PropertyValue>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let existing = Self::validate_existing_location(&location_id)?;
+			let owner = <PikeModule<T>>::validate_org_index(existing.owner)?;
+			let location = LocationBuilder::default()
+				.with_id(location_id.clone())
+				.with_namespace(existing.location_namespace)
+				.with_owner(existing.owner)
+				.with_properties(properties)
+				.build()?;
+			ensure_can_update::<T>(&sender, &owner, PERM_CAN_UPDATE_LOCATION, LOCATION_SCHEMA, &location.properties)?;
+
+			<Locations<T>>::insert(&location_id, location);
+			<SchemaModule<T>>::warn_if_deprecated(LOCATION_SCHEMA);
+
+			Self::deposit_event(Event::LocationUpdated(location_id, owner));
+
+			Ok(())
+		}
+
+		pub fn delete_location(origin, location_id: LocationId) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let location = Self::validate_existing_location(&location_id)?;
+			let owner = <PikeModule<T>>::validate_org_index(location.owner)?;
+
+			// Validate signer is an active agent of the owner org,
+			// with the right to delete location
+			<PikeModule<T>>::ensure_permission(&sender, owner.clone(), PERM_CAN_DELETE_LOCATION)?;
+
+			<Locations<T>>::remove(&location_id);
+
+			Self::deposit_event(Event::LocationDeleted(location_id, owner));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	// PUBLIC IMMUTABLES
+
+	/// Checks that a location exists, so that other modules can reference it.
+	pub fn validate_existing_location(location_id: &[u8]) -> rstd::result::Result<Location, &'static str> {
+		<Locations<T>>::get(location_id.to_vec()).ok_or(ERR_LOCATION_DOES_NOT_EXIST)
+	}
+
+	// Helpers
+	fn validate_new_location(location_id: &[u8]) -> Result {
+		ensure!(!<Locations<T>>::exists(location_id.to_vec()), ERR_LOCATION_ALREADY_EXISTS);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::grid_pike::{
+		ERR_ORG_DOES_NOT_EXIST, ERR_SENDER_MUST_BE_ORG_AGENT, ERR_SENDER_MISSING_PERMISSION};
+	use crate::grid_gs1::tests::{self as gs1, org, store_test_editor, store_other_admin};
+	use crate::grid_pike::Trait as PikeTrait;
+	use crate::grid_pike::tests::test_org_index;
+	use crate::grid_schema::{
+		PropertyDefinition, DataType, SchemaStatus, Value,
+		ERR_PROPERTY_VALUE_REQUIRED, ERR_PROPERTY_VALUE_LATLONG_OUT_OF_BOUNDS, ERR_SCHEMA_RETIRED};
+	use crate::grid_schema::tests::set_test_schema_status;
+
+	use primitives::{Blake2Hasher, H256};
+	use runtime_io::with_externalities;
+	use runtime_primitives::{
+		testing::{Digest, DigestItem, Header},
+		traits::{BlakeTwo256, IdentityLookup},
+		BuildStorage,
+	};
+	use support::{assert_noop, assert_ok, impl_outer_origin};
+
+	impl_outer_origin! {
+		pub enum Origin for GridLocationTest {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct GridLocationTest;
+
+	impl system::Trait for GridLocationTest {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type Digest = Digest;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type Log = DigestItem;
+	}
+	impl PikeTrait for GridLocationTest {
+		type Event = ();
+	}
+	impl SchemaTrait for GridLocationTest {
+		type Event = ();
+	}
+	impl Trait for GridLocationTest {
+		type Event = ();
+	}
+
+	type GridLocation = super::Module<GridLocationTest>;
+
+	fn build_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let t = system::GenesisConfig::<GridLocationTest>::default()
+			.build_storage()
+			.unwrap()
+			.0;
+		t.into()
+	}
+
+	const TEST_GLN: &[u8] = b"0614141000418";
+
+	fn location(name: &[u8], latitude: i64, longitude: i64) -> Vec<PropertyValue> {
+		vec![
+			PropertyValue { name: b"location_name".to_vec(), value: Value::String(name.to_vec()) },
+			PropertyValue { name: b"coordinates".to_vec(), value: Value::LatLong(latitude, longitude) },
+		]
+	}
+
+	fn test_properties() -> Vec<PropertyValue> {
+		location(b"Main warehouse", 50_850_346, 4_351_721)
+	}
+
+	fn store_test_admin(admin: u64) -> OrgId {
+		gs1::store_test_admin(
+			admin,
+			LOCATION_SCHEMA,
+			vec![
+				PropertyDefinition { name: b"location_name".to_vec(), data_type: DataType::String, required: true, ..Default::default() },
+				PropertyDefinition { name: b"coordinates".to_vec(), data_type: DataType::LatLong, ..Default::default() },
+			]
+		)
+	}
+
+	fn store_test_location(owner: OrgId) {
+		Locations::<GridLocationTest>::insert(
+			TEST_GLN.to_vec(),
+			Location {
+				location_id: TEST_GLN.to_vec(),
+				location_namespace: LocationNamespace::GS1,
+				owner: test_org_index(&owner),
+				properties: test_properties(),
+			},
+		);
+	}
+
+	#[test]
+	fn gln_validation() {
+		assert_ok!(validate_gln(TEST_GLN));
+		assert_ok!(validate_gln(b"4006381333931"));
+		assert_eq!(validate_gln(b"0614141000419"), Err(ERR_LOCATION_ID_INVALID_GLN));
+		// GTIN-14 is not a GLN
+		assert_eq!(validate_gln(b"10614141000415"), Err(ERR_LOCATION_ID_INVALID_GLN));
+	}
+
+	// create_location tests
+	#[test]
+	fn create_location_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+
+			let result = GridLocation::create_location(
+				Origin::signed(sender), TEST_GLN.to_vec(), LocationNamespace::GS1,
+				owner.clone(), test_properties());
+
+			assert_ok!(result);
+
+			assert_eq!(
+				GridLocation::location_by_id(TEST_GLN.to_vec()),
+				Some(Location {
+					location_id: TEST_GLN.to_vec(),
+					location_namespace: LocationNamespace::GS1,
+					owner: test_org_index(&owner),
+					properties: test_properties(),
+				})
+			);
+		})
+	}
+
+	#[test]
+	fn create_location_with_missing_id() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridLocation::create_location(
+					Origin::signed(1), vec!(), LocationNamespace::GS1,
+					org(), vec!()),
+				ERR_LOCATION_ID_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_location_with_invalid_gln() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridLocation::create_location(
+					Origin::signed(1), b"0614141000419".to_vec(), LocationNamespace::GS1,
+					org(), vec!()),
+				ERR_LOCATION_ID_INVALID_GLN
+			);
+		})
+	}
+
+	#[test]
+	fn create_location_with_existing_id() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			store_test_location(owner.clone());
+
+			assert_noop!(
+				GridLocation::create_location(
+					Origin::signed(sender), TEST_GLN.to_vec(), LocationNamespace::GS1, owner, vec!()),
+				ERR_LOCATION_ALREADY_EXISTS
+			);
+		})
+	}
+
+	#[test]
+	fn create_location_with_unknown_org() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridLocation::create_location(
+					Origin::signed(1), TEST_GLN.to_vec(), LocationNamespace::GS1,
+					org(), vec!()),
+				ERR_ORG_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn create_location_without_permission() {
+		with_externalities(&mut build_ext(), || {
+			let agent = 1;
+			let owner = store_test_editor(agent, PERM_CAN_UPDATE_LOCATION);
+
+			assert_noop!(
+				GridLocation::create_location(
+					Origin::signed(agent), TEST_GLN.to_vec(), LocationNamespace::GS1, owner, vec!()),
+				ERR_SENDER_MISSING_PERMISSION
+			);
+		})
+	}
+
+	#[test]
+	fn create_location_with_invalid_properties() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+
+			assert_noop!(
+				GridLocation::create_location(
+					Origin::signed(sender), TEST_GLN.to_vec(), LocationNamespace::GS1, owner.clone(), vec!()),
+				ERR_PROPERTY_VALUE_REQUIRED
+			);
+			assert_noop!(
+				GridLocation::create_location(
+					Origin::signed(sender), TEST_GLN.to_vec(), LocationNamespace::GS1,
+					owner, location(b"Main warehouse", 95_000_000, 0)),
+				ERR_PROPERTY_VALUE_LATLONG_OUT_OF_BOUNDS
+			);
+		})
+	}
+
+	#[test]
+	fn create_location_with_retired_schema() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			set_test_schema_status(LOCATION_SCHEMA, SchemaStatus::Retired);
+
+			assert_noop!(
+				GridLocation::create_location(
+					Origin::signed(sender), TEST_GLN.to_vec(), LocationNamespace::GS1, owner, test_properties()),
+				ERR_SCHEMA_RETIRED
+			);
+		})
+	}
+
+	// update_location tests
+	#[test]
+	fn update_location_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			store_test_location(owner.clone());
+
+			let properties = location(b"North warehouse", 51_219_448, 4_402_464);
+			assert_ok!(GridLocation::update_location(
+				Origin::signed(sender), TEST_GLN.to_vec(), properties.clone()));
+
+			assert_eq!(
+				GridLocation::location_by_id(TEST_GLN.to_vec()).map(|l| l.properties),
+				Some(properties)
+			);
+		})
+	}
+
+	#[test]
+	fn update_location_with_unknown_location() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridLocation::update_location(Origin::signed(1), TEST_GLN.to_vec(), vec!()),
+				ERR_LOCATION_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn update_location_from_other_org() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let other = 2;
+			let owner = store_test_admin(sender);
+			store_test_location(owner);
+
+			store_other_admin(other);
+
+			assert_noop!(
+				GridLocation::update_location(Origin::signed(other), TEST_GLN.to_vec(), vec!()),
+				ERR_SENDER_MUST_BE_ORG_AGENT
+			);
+		})
+	}
+
+	// delete_location tests
+	#[test]
+	fn delete_location_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			store_test_location(owner);
+
+			assert_ok!(GridLocation::delete_location(Origin::signed(sender), TEST_GLN.to_vec()));
+
+			assert_eq!(GridLocation::location_by_id(TEST_GLN.to_vec()), None);
+		})
+	}
+
+	#[test]
+	fn delete_location_without_permission() {
+		with_externalities(&mut build_ext(), || {
+			let agent = 1;
+			let owner = store_test_editor(agent, PERM_CAN_UPDATE_LOCATION);
+			store_test_location(owner);
+
+			assert_noop!(
+				GridLocation::delete_location(Origin::signed(agent), TEST_GLN.to_vec()),
+				ERR_SENDER_MISSING_PERMISSION
+			);
+		})
+	}
+}
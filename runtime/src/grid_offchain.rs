@@ -0,0 +1,148 @@
+// Copyright 2019 Steve Degosserie
+// Anchors of data kept off-chain, shared by the Grid runtime modules
+
+use rstd::prelude::*;
+use parity_codec::{Decode, Encode};
+use primitives::H256;
+#[cfg(feature = "std")]
+use serde_derive::{Deserialize, Serialize};
+use support::{ensure, dispatch::Result};
+use crate::grid_cid::parse_cid;
+
+pub const ERR_OFFCHAIN_SIZE_REQUIRED: &str = "Off-chain data size required";
+pub const ERR_OFFCHAIN_URI_REQUIRED: &str = "Off-chain data URI cannot be empty";
+pub const ERR_OFFCHAIN_URI_TOO_LONG: &str = "Off-chain data URI too long";
+pub const ERR_OFFCHAIN_SIZE_MISMATCH: &str = "Preimage size does not match off-chain reference";
+pub const ERR_OFFCHAIN_DIGEST_MISMATCH: &str = "Preimage digest does not match off-chain reference";
+
+// Fits CIDs as well as common URLs
+const URI_LIMIT: usize = 256;
+// URIs of IPFS content, whose CID is validated
+const IPFS_SCHEME: &[u8] = b"ipfs://";
+
+/// Hash function of an off-chain reference digest.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+	Blake2_256,
+	Keccak256,
+}
+
+impl Default for HashAlgorithm {
+	fn default() -> Self {
+		HashAlgorithm::Blake2_256
+	}
+}
+
+impl HashAlgorithm {
+	pub fn hash(&self, data: &[u8]) -> H256 {
+		match self {
+			HashAlgorithm::Blake2_256 => runtime_io::blake2_256(data).into(),
+			HashAlgorithm::Keccak256 => runtime_io::keccak_256(data).into(),
+		}
+	}
+}
+
+/// Anchors data kept off-chain (e.g. documents, images, long descriptions) by its hash,
+/// so that it can be stored in place of the data itself while remaining tamper-evident.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct OffchainRef {
+	pub algorithm: HashAlgorithm,
+	pub digest: H256,
+	// Size of the data, in bytes
+	pub size: u64,
+	// Where the data can be fetched from, e.g. an URL or an `ipfs://<CID>`
+	pub uri: Option<Vec<u8>>,
+}
+
+impl OffchainRef {
+	/// Anchors the given data.
+	pub fn of(algorithm: HashAlgorithm, data: &[u8], uri: Option<Vec<u8>>) -> Self {
+		OffchainRef {
+			algorithm,
+			digest: algorithm.hash(data),
+			size: data.len() as u64,
+			uri,
+		}
+	}
+}
+
+pub fn validate_offchain_ref(offchain_ref: &OffchainRef) -> Result {
+	ensure!(offchain_ref.size > 0, ERR_OFFCHAIN_SIZE_REQUIRED);
+	if let Some(uri) = &offchain_ref.uri {
+		ensure!(uri.len() > 0, ERR_OFFCHAIN_URI_REQUIRED);
+		ensure!(uri.len() <= URI_LIMIT, ERR_OFFCHAIN_URI_TOO_LONG);
+		if uri.starts_with(IPFS_SCHEME) {
+			parse_cid(&uri[IPFS_SCHEME.len()..])?;
+		}
+	}
+	Ok(())
+}
+
+/// Checks that a preimage, e.g. data fetched off-chain, is the one anchored by the reference.
+pub fn verify_preimage(offchain_ref: &OffchainRef, preimage: &[u8]) -> Result {
+	ensure!(preimage.len() as u64 == offchain_ref.size, ERR_OFFCHAIN_SIZE_MISMATCH);
+	ensure!(offchain_ref.algorithm.hash(preimage) == offchain_ref.digest, ERR_OFFCHAIN_DIGEST_MISMATCH);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::grid_cid::{ERR_CID_INVALID_ENCODING, ERR_CID_REQUIRED};
+
+	const DOCUMENT: &[u8] = b"GS1 product sheet: 12 units of 250g";
+	const URI: &[u8] = b"ipfs://bafkreigh2akiscaildcqabsyg3dfr6chu3fgpregiymsck7e7aqa4s52zy";
+
+	#[test]
+	fn verify_preimage_with_anchored_data() {
+		for algorithm in &[HashAlgorithm::Blake2_256, HashAlgorithm::Keccak256] {
+			let offchain_ref = OffchainRef::of(*algorithm, DOCUMENT, Some(URI.to_vec()));
+
+			assert_eq!(validate_offchain_ref(&offchain_ref), Ok(()));
+			assert_eq!(verify_preimage(&offchain_ref, DOCUMENT), Ok(()));
+		}
+	}
+
+	#[test]
+	fn verify_preimage_with_tampered_data() {
+		let offchain_ref = OffchainRef::of(HashAlgorithm::Blake2_256, DOCUMENT, None);
+		let mut tampered = DOCUMENT.to_vec();
+		tampered[0] = b'X';
+
+		assert_eq!(verify_preimage(&offchain_ref, &tampered), Err(ERR_OFFCHAIN_DIGEST_MISMATCH));
+		assert_eq!(verify_preimage(&offchain_ref, &DOCUMENT[1..]), Err(ERR_OFFCHAIN_SIZE_MISMATCH));
+	}
+
+	#[test]
+	fn verify_preimage_with_other_algorithm() {
+		let mut offchain_ref = OffchainRef::of(HashAlgorithm::Blake2_256, DOCUMENT, None);
+		offchain_ref.algorithm = HashAlgorithm::Keccak256;
+
+		assert_eq!(verify_preimage(&offchain_ref, DOCUMENT), Err(ERR_OFFCHAIN_DIGEST_MISMATCH));
+	}
+
+	#[test]
+	fn validate_offchain_ref_with_invalid_args() {
+		let mut offchain_ref = OffchainRef::of(HashAlgorithm::Blake2_256, DOCUMENT, Some(vec!()));
+		assert_eq!(validate_offchain_ref(&offchain_ref), Err(ERR_OFFCHAIN_URI_REQUIRED));
+
+		offchain_ref.uri = Some(vec![b'a'; URI_LIMIT + 1]);
+		assert_eq!(validate_offchain_ref(&offchain_ref), Err(ERR_OFFCHAIN_URI_TOO_LONG));
+
+		offchain_ref.uri = Some(b"ipfs://".to_vec());
+		assert_eq!(validate_offchain_ref(&offchain_ref), Err(ERR_CID_REQUIRED));
+		offchain_ref.uri = Some(URI[..URI.len() - 1].to_vec());
+		assert_eq!(validate_offchain_ref(&offchain_ref), Err(ERR_CID_INVALID_ENCODING));
+
+		// Other URIs are taken as-is
+		offchain_ref.uri = Some(b"https://example.com/sheet.pdf".to_vec());
+		assert_eq!(validate_offchain_ref(&offchain_ref), Ok(()));
+
+		assert_eq!(
+			validate_offchain_ref(&OffchainRef::of(HashAlgorithm::Blake2_256, b"", None)),
+			Err(ERR_OFFCHAIN_SIZE_REQUIRED)
+		);
+	}
+}
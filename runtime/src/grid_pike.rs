@@ -3,11 +3,13 @@
 
 use parity_codec::{Decode, Encode};
 use rstd::prelude::*;
-// use runtime_io::{with_storage, StorageOverlay, ChildrenStorageOverlay};
-// use runtime_primitives::traits::Hash;``
+#[cfg(feature = "std")]
+use runtime_io::with_storage;
+#[cfg(feature = "std")]
+use serde_derive::{Deserialize, Serialize};
 use support::{decl_event, decl_module, decl_storage,
     dispatch::Result, ensure, fail, StorageValue, StorageMap};
-use system::ensure_signed;
+use system::{ensure_root, ensure_signed};
 
 pub const ERR_ORG_ID_REQUIRED: &str = "Organization ID required";
 pub const ERR_ORG_ID_TOO_LONG: &str = "Organization ID too long";
@@ -16,18 +18,34 @@ pub const ERR_ORG_NAME_TOO_LONG: &str = "Organization name too long";
 pub const ERR_ORG_ALREADY_EXISTS: &str = "Organization already exists";
 pub const ERR_ORG_DOES_NOT_EXIST: &str = "Organization does not exist";
 pub const ERR_AGENT_ALREADY_EXISTS: &str = "Agent already exists";
+pub const ERR_AGENT_DOES_NOT_EXIST: &str = "Agent does not exist";
+pub const ERR_AGENT_NOT_IN_ORG: &str = "Agent must belong to the specified organization";
+pub const ERR_ORG_MUST_KEEP_ACTIVE_ADMIN: &str = "Organization must keep at least one active admin";
+pub const ERR_ROLE_NAME_REQUIRED: &str = "Role name required";
+pub const ERR_ROLE_NAME_TOO_LONG: &str = "Role name too long";
+pub const ERR_ROLE_DESCRIPTION_TOO_LONG: &str = "Role description too long";
+pub const ERR_ROLE_ALREADY_EXISTS: &str = "Role already exists";
+pub const ERR_ROLE_DOES_NOT_EXIST: &str = "Role does not exist";
+pub const ERR_ROLE_IS_RESERVED: &str = "Admin role cannot be modified";
+pub const ERR_PERMISSION_REQUIRED: &str = "Permission required";
+pub const ERR_PERMISSION_TOO_LONG: &str = "Permission too long";
+pub const ERR_PERMISSION_DUPLICATED: &str = "Permission listed more than once";
 pub const ERR_SENDER_IS_NOT_AN_AGENT: &str = "Sender must be a known organization agent";
 pub const ERR_SENDER_MUST_BE_ORG_AGENT: &str = "Sender must be agent of the specified organization";
 pub const ERR_SENDER_MUST_BE_ORG_ADMIN: &str = "Sender must be organization admin";
 pub const ERR_SENDER_MUST_BE_ACTIVE_ADMIN: &str = "Sender must be an active organization admin";
+pub const ERR_SENDER_MISSING_PERMISSION: &str = "Sender does not have the required permission";
 
 pub const BYTEARRAY_LIMIT: usize = 100;
+pub const DESCRIPTION_LIMIT: usize = 256;
 pub const ROLE_ADMIN: &[u8; 5] = b"admin";
 
 // This could be a DID
 pub type OrgId = Vec<u8>;
 pub type OrgName = Vec<u8>;
-pub type Role = Vec<u8>;
+pub type RoleName = Vec<u8>;
+// e.g. "schema::can-create-schema"
+pub type Permission = Vec<u8>;
 
 pub fn validate_org_id(id: &[u8]) -> Result {
     ensure!(id.len() > 0, ERR_ORG_ID_REQUIRED);
@@ -41,14 +59,29 @@ fn validate_org_name(name: &[u8]) -> Result {
     Ok(())
 }
 
-#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
 pub struct Organization {
     pub id: OrgId,
     pub name: OrgName,
 }
 
-#[cfg_attr(feature = "std", derive(Debug))]
+fn validate_role_name(name: &[u8]) -> Result {
+    ensure!(name.len() > 0, ERR_ROLE_NAME_REQUIRED);
+    ensure!(name.len() <= BYTEARRAY_LIMIT, ERR_ROLE_NAME_TOO_LONG);
+    Ok(())
+}
+
+fn validate_permissions(permissions: &[Permission]) -> Result {
+    for (i, permission) in permissions.iter().enumerate() {
+        ensure!(permission.len() > 0, ERR_PERMISSION_REQUIRED);
+        ensure!(permission.len() <= BYTEARRAY_LIMIT, ERR_PERMISSION_TOO_LONG);
+        ensure!(!permissions[..i].contains(permission), ERR_PERMISSION_DUPLICATED);
+    }
+    Ok(())
+}
+
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
 pub struct Agent<AccountId> {
     pub org_id: OrgId,
@@ -57,6 +90,16 @@ pub struct Agent<AccountId> {
     pub role_ids: Vec<u32>,
 }
 
+/// A role is defined by, and only meaningful within, its owning organization.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct Role {
+    pub org_id: OrgId,
+    pub name: RoleName,
+    pub description: Vec<u8>,
+    pub permissions: Vec<Permission>,
+}
+
 #[derive(Default)]
 pub struct OrganizationBuilder {
     id: OrgId,
@@ -117,32 +160,112 @@ impl<AccountId: Default> AgentBuilder<AccountId> {
     }
 }
 
+#[derive(Default)]
+pub struct RoleBuilder {
+    org_id: OrgId,
+    name: RoleName,
+    description: Vec<u8>,
+    permissions: Vec<Permission>,
+}
+impl RoleBuilder {
+    pub fn with_org(mut self, org_id: OrgId) -> Self {
+        self.org_id = org_id;
+        self
+    }
+
+    pub fn with_name(mut self, name: RoleName) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_description(mut self, description: Vec<u8>) -> Self {
+        self.description = description;
+        self
+    }
+
+    pub fn with_permissions(mut self, permissions: Vec<Permission>) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    pub fn build(self) -> rstd::result::Result<Role, &'static str> {
+        validate_org_id(&self.org_id)?;
+        validate_role_name(&self.name)?;
+        ensure!(self.description.len() <= DESCRIPTION_LIMIT, ERR_ROLE_DESCRIPTION_TOO_LONG);
+        validate_permissions(&self.permissions)?;
+        let mut role = Role::default();
+        role.org_id = self.org_id;
+        role.name = self.name;
+        role.description = self.description;
+        role.permissions = self.permissions;
+        Ok(role)
+    }
+}
+
 pub trait Trait: system::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as GridPike {
-        Organizations get(org_by_id): map OrgId => Option<Organization>;
-        Agents get(agent_by_account): map T::AccountId => Option<Agent<T::AccountId>>;
-
-        Roles get(role_by_index): map u32 => Role;
+        // Organizations of the previous layout, keyed by ID, moved to Orgs by `migrate_orgs`
+        Organizations get(legacy_org): map OrgId => Option<Organization>;
+        Orgs get(org_by_index): map u32 => Option<Organization>;
+        OrgsCount get(orgs_count): u32;
+        OrgsIndex get(org_index): map OrgId => u32;
+
+        // Single-organization agents of the previous layout, moved to Memberships by `migrate_agents`
+        Agents get(legacy_agent): map T::AccountId => Option<Agent<T::AccountId>>;
+        // An account acts as a separate agent, with its own active flag & roles, for each organization index
+        Memberships: map (T::AccountId, u32) => Option<Agent<T::AccountId>>;
+        // Indexes of the organizations an account acts for, in order of membership
+        IndexedAgentOrgs get(agent_org_by_index): map (T::AccountId, u32) => u32;
+        AgentOrgsCount get(agent_orgs_count): map T::AccountId => u32;
+        // Accounts of the agents of an organization index, in order of creation
+        OrgAgents get(org_agent_by_index): map (u32, u32) => T::AccountId;
+        OrgAgentsCount get(org_agents_count): map u32 => u32;
+
+        // Global role names of the previous layout, still referenced by the role IDs of legacy agents.
+        // Roles of organizations share their IDs, so that legacy IDs never resolve to them.
+        Roles get(legacy_role): map u32 => Option<RoleName>;
+        OrgRoles get(role_by_index): map u32 => Option<Role>;
         RolesCount get(roles_count): u32;
-        RolesIndex get(role_index): map Role => u32;
-    }
+        OrgRolesIndex get(role_index): map (u32, RoleName) => u32;
 
-    //FIXME: does not compile -> tests setup storage data inline instead
-    // add_extra_genesis {
-    // 	config(orgs): Vec<(OrgId, OrgName)>;
+        // Number of active agents holding the admin role, per organization index
+        ActiveAdminsCount get(active_admins_count): map u32 => u32;
+    }
 
-    // 	build(|storage: &mut StorageOverlay, _: &mut ChildrenStorageOverlay, config: &GenesisConfig<T>| {
-    // 		with_storage(storage, || {
-    // 			for &(ref id, name) in &config.orgs {
-    // 				let _ = <Module<T>>::create_org(id.clone(), name.clone());
-    // 			}
-    // 		});
-    // 	});
-    // }
+    add_extra_genesis {
+        // Organizations with their (active) admin agent
+        config(orgs): Vec<(OrgId, OrgName, T::AccountId)>;
+        // Roles defined by the above organizations
+        config(roles): Vec<(OrgId, RoleName, Vec<u8>, Vec<Permission>)>;
+
+        build(|storage: &mut runtime_primitives::StorageOverlay,
+               _: &mut runtime_primitives::ChildrenStorageOverlay,
+               config: &GenesisConfig<T>| {
+            with_storage(storage, || {
+                for (id, name, admin) in &config.orgs {
+                    <Module<T>>::add_org(id.clone(), name.clone(), admin.clone())
+                        .expect("Invalid genesis organization");
+                }
+
+                for (org_id, name, description, permissions) in &config.roles {
+                    let role = RoleBuilder::default()
+                        .with_org(org_id.clone())
+                        .with_name(name.clone())
+                        .with_description(description.clone())
+                        .with_permissions(permissions.clone())
+                        .build()
+                        .expect("Invalid genesis role");
+                    <Module<T>>::validate_existing_org(org_id).expect("Unknown genesis role organization");
+                    <Module<T>>::validate_new_role(org_id, name).expect("Duplicate genesis role");
+                    <Module<T>>::add_role(role).expect("Too many genesis roles");
+                }
+            });
+        });
+    }
 }
 
 decl_event!(
@@ -150,7 +273,15 @@ decl_event!(
 	where <T as system::Trait>::AccountId
 	{
 		OrganizationCreated(OrgId, OrgName),
+		OrganizationUpdated(OrgId, OrgName),
 		AgentCreated(OrgId, AccountId),
+		AgentUpdated(OrgId, AccountId),
+		RoleCreated(OrgId, RoleName),
+		RoleUpdated(OrgId, RoleName),
+		RoleDeleted(OrgId, RoleName),
+		AgentsMigrated(u32),
+		OrgsMigrated(u32),
+		OrgAgentsBackfilled(OrgId, u32),
 	}
 );
 
@@ -161,35 +292,37 @@ decl_module! {
         pub fn create_org(origin, id: OrgId, name: OrgName) -> Result {
             let sender = ensure_signed(origin)?;
 
+            Self::add_org(id.clone(), name.clone(), sender.clone())?;
+
+            Self::deposit_event(RawEvent::OrganizationCreated(id.clone(), name));
+            Self::deposit_event(RawEvent::AgentCreated(id, sender));
+
+            Ok(())
+        }
+
+        pub fn update_org(origin, id: OrgId, name: OrgName) -> Result {
+            let sender = ensure_signed(origin)?;
+
             let org = OrganizationBuilder::default()
                 .with_id(id.clone())
                 .with_name(name.clone())
                 .build()?;
-            Self::validate_new_org(&id)?;
-
-            let mut agent = AgentBuilder::<T::AccountId>::default()
-                .with_org(id.clone())
-                .with_account(sender.clone())
-                .is_active(true)
-                .build()?;
-            Self::validate_new_agent(&sender)?;
+            Self::validate_existing_org(&id)?;
 
-            // Do this after all valitadions cause we're potentially mutating state
-            let admin_role_id = Self::get_or_add_role_id(ROLE_ADMIN.to_vec())?;
-            agent.role_ids = vec![admin_role_id];
+            // verify the signer of the transaction is authorized to update org
+            Self::validate_is_org_active_agent(&sender, id.clone())?;
+            Self::validate_is_agent_admin(&sender, &id)?;
 
-			<Organizations<T>>::insert(&id, org);
-            <Agents<T>>::insert(&sender, agent);
+            Self::insert_org(org)?;
 
-            Self::deposit_event(RawEvent::OrganizationCreated(id.clone(), name));
-            Self::deposit_event(RawEvent::AgentCreated(id, sender));
+            Self::deposit_event(RawEvent::OrganizationUpdated(id, name));
 
             Ok(())
         }
 
         pub fn create_agent(
             origin, org_id: OrgId, account: T::AccountId,
-            active: bool, roles: Vec<Role>) -> Result {
+            active: bool, roles: Vec<RoleName>) -> Result {
             let sender = ensure_signed(origin)?;
 
             let mut agent = AgentBuilder::<T::AccountId>::default()
@@ -197,32 +330,270 @@ decl_module! {
                 .with_account(account.clone())
                 .is_active(active)
                 .build()?;
-            Self::validate_new_agent(&account)?;
+            Self::validate_new_agent(&account, &org_id)?;
 			Self::validate_existing_org(&org_id)?;
 
             // verify the signer of the transaction is authorized to create agent
             Self::validate_is_org_active_agent(&sender, org_id.clone())?;
-            Self::validate_is_agent_admin(&sender)?;
+            Self::validate_is_agent_admin(&sender, &org_id)?;
 
-            // Do this after all valitadions cause we're potentially mutating state
-            agent.role_ids = Self::get_or_add_roles(roles)?;
+            let is_active_admin = active && Self::contains_admin_role(&roles);
+            agent.role_ids = Self::get_role_ids(&org_id, roles)?;
 
-            <Agents<T>>::insert(&account, agent);
+            let org_idx = Self::index_org(&org_id)?;
+            Self::add_org_agent(org_idx, &account)?;
+            Self::insert_agent(agent)?;
+            if is_active_admin {
+                Self::increment_active_admins(org_idx)?;
+            }
 
             Self::deposit_event(RawEvent::AgentCreated(org_id, account));
 
             Ok(())
         }
+
+        pub fn update_agent(
+            origin, org_id: OrgId, account: T::AccountId,
+            active: bool, roles: Vec<RoleName>) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            validate_org_id(&org_id)?;
+			Self::validate_existing_org(&org_id)?;
+            let mut agent = Self::validate_existing_org_agent(&account, &org_id)?;
+
+            // verify the signer of the transaction is authorized to update agent
+            Self::validate_is_org_active_agent(&sender, org_id.clone())?;
+            Self::validate_is_agent_admin(&sender, &org_id)?;
+
+            // An admin must not be able to lock the organization out
+            let was_active_admin = agent.active && Self::has_admin_role(&agent);
+            let is_active_admin = active && Self::contains_admin_role(&roles);
+            if was_active_admin && !is_active_admin {
+                ensure!(
+                    Self::find_org_index(&org_id).map_or(0, Self::active_admins_count) > 1,
+                    ERR_ORG_MUST_KEEP_ACTIVE_ADMIN
+                );
+            }
+
+            agent.active = active;
+            agent.role_ids = Self::get_role_ids(&org_id, roles)?;
+
+            let org_idx = Self::index_org(&org_id)?;
+            Self::insert_agent(agent)?;
+            if !was_active_admin && is_active_admin {
+                Self::increment_active_admins(org_idx)?;
+            } else if was_active_admin && !is_active_admin {
+                Self::decrement_active_admins(org_idx);
+            }
+
+            Self::deposit_event(RawEvent::AgentUpdated(org_id, account));
+
+            Ok(())
+        }
+
+        pub fn create_role(
+            origin, org_id: OrgId, name: RoleName,
+            description: Vec<u8>, permissions: Vec<Permission>) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            let role = RoleBuilder::default()
+                .with_org(org_id.clone())
+                .with_name(name.clone())
+                .with_description(description)
+                .with_permissions(permissions)
+                .build()?;
+			Self::validate_existing_org(&org_id)?;
+            Self::validate_new_role(&org_id, &name)?;
+
+            // verify the signer of the transaction is authorized to create role
+            Self::validate_is_org_active_agent(&sender, org_id.clone())?;
+            Self::validate_is_agent_admin(&sender, &org_id)?;
+
+            Self::add_role(role)?;
+
+            Self::deposit_event(RawEvent::RoleCreated(org_id, name));
+
+            Ok(())
+        }
+
+        pub fn update_role(
+            origin, org_id: OrgId, name: RoleName,
+            description: Vec<u8>, permissions: Vec<Permission>) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            let role = RoleBuilder::default()
+                .with_org(org_id.clone())
+                .with_name(name.clone())
+                .with_description(description)
+                .with_permissions(permissions)
+                .build()?;
+			Self::validate_existing_org(&org_id)?;
+            let role_id = Self::validate_existing_role(&org_id, &name)?;
+            ensure!(name.as_slice() != &ROLE_ADMIN[..], ERR_ROLE_IS_RESERVED);
+
+            // verify the signer of the transaction is authorized to update role
+            Self::validate_is_org_active_agent(&sender, org_id.clone())?;
+            Self::validate_is_agent_admin(&sender, &org_id)?;
+
+            <OrgRoles<T>>::insert(role_id, role);
+
+            Self::deposit_event(RawEvent::RoleUpdated(org_id, name));
+
+            Ok(())
+        }
+
+        pub fn delete_role(origin, org_id: OrgId, name: RoleName) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            validate_org_id(&org_id)?;
+            validate_role_name(&name)?;
+			Self::validate_existing_org(&org_id)?;
+            let role_id = Self::validate_existing_role(&org_id, &name)?;
+            ensure!(name.as_slice() != &ROLE_ADMIN[..], ERR_ROLE_IS_RESERVED);
+
+            // verify the signer of the transaction is authorized to delete role
+            Self::validate_is_org_active_agent(&sender, org_id.clone())?;
+            Self::validate_is_agent_admin(&sender, &org_id)?;
+
+            // Agents may still reference the role ID, but it no longer resolves
+            // and is never reused, so those references are simply ignored.
+            <OrgRoles<T>>::remove(role_id);
+            <OrgRolesIndex<T>>::remove((Self::org_index(&org_id), name.clone()));
+
+            Self::deposit_event(RawEvent::RoleDeleted(org_id, name));
+
+            Ok(())
+        }
+
+        /// Moves agents of the previous single-organization layout to per-organization memberships.
+        /// Their global roles are replaced by roles of their organization with the same name,
+        /// created along the way, without permissions (admins hold every permission).
+        /// Legacy agents are keyed by account, which can't be enumerated on-chain: the accounts are
+        /// those of the `AgentCreated` events emitted before the upgrade, e.g. collected by an indexer.
+        /// Accounts without such a legacy agent are skipped, so batches can safely be replayed.
+        pub fn migrate_agents(origin, accounts: Vec<T::AccountId>) -> Result {
+            ensure_root(origin)?;
+
+            let mut migrated: u32 = 0;
+            for account in accounts {
+                if let Some(mut agent) = <Agents<T>>::get(&account) {
+                    let mut role_ids: Vec<u32> = vec!();
+                    for role_id in &agent.role_ids {
+                        if let Some(name) = Self::legacy_role(role_id) {
+                            role_ids.push(Self::get_or_add_role(&agent.org_id, name)?);
+                        }
+                    }
+                    agent.role_ids = role_ids;
+                    Self::insert_agent(agent)?;
+                    migrated += 1;
+                }
+            }
+
+            Self::deposit_event(RawEvent::AgentsMigrated(migrated));
+
+            Ok(())
+        }
+
+        /// Indexes the agents of an organization created before agents were indexed by organization,
+        /// e.g. the accounts of its `AgentCreated` events, then recounts its active admins from the index.
+        /// Accounts that are not agents of the organization, or already indexed, are skipped,
+        /// so batches can safely be replayed. Until every agent is indexed, admins can only be undercounted.
+        pub fn backfill_org_agents(origin, org_id: OrgId, accounts: Vec<T::AccountId>) -> Result {
+            ensure_root(origin)?;
+
+            Self::validate_existing_org(&org_id)?;
+            let org_idx = Self::index_org(&org_id)?;
+
+            let mut indexed: Vec<T::AccountId> = (0..Self::org_agents_count(org_idx))
+                .map(|i| Self::org_agent_by_index((org_idx, i)))
+                .collect();
+            let mut backfilled: u32 = 0;
+            for account in accounts {
+                if !indexed.contains(&account) && Self::agent_of_org(&account, &org_id).is_some() {
+                    Self::add_org_agent(org_idx, &account)?;
+                    indexed.push(account);
+                    backfilled += 1;
+                }
+            }
+
+            let active_admins = Self::agents_of_org(&org_id).iter()
+                .filter(|agent| agent.active && Self::has_admin_role(agent))
+                .count() as u32;
+            <ActiveAdminsCount<T>>::insert(org_idx, active_admins);
+
+            Self::deposit_event(RawEvent::OrgAgentsBackfilled(org_id, backfilled));
+
+            Ok(())
+        }
+
+        /// Moves organizations of the previous layout, keyed by ID, to the u32 indexed one,
+        /// and defines their admin role, so that their legacy admins act as such.
+        /// IDs without such a legacy organization are skipped, so batches can safely be replayed.
+        pub fn migrate_orgs(origin, ids: Vec<OrgId>) -> Result {
+            ensure_root(origin)?;
+
+            let mut migrated: u32 = 0;
+            for id in ids {
+                if let Some(org) = <Organizations<T>>::get(&id) {
+                    Self::insert_org(org)?;
+                    migrated += 1;
+                }
+            }
+
+            Self::deposit_event(RawEvent::OrgsMigrated(migrated));
+
+            Ok(())
+        }
     }
 }
 
 impl<T: Trait> Module<T> {
     // PUBLIC IMMUTABLES
 
+    /// Returns the agent acting for an account within the specified organization.
+    /// Agents not migrated yet are still found in the legacy layout,
+    /// holding the roles of their organization named after their global ones.
+    pub fn agent_of_org(account: &T::AccountId, org_id: &[u8]) -> Option<Agent<T::AccountId>> {
+        Self::find_org_index(org_id)
+            .and_then(|org_idx| <Memberships<T>>::get((account.clone(), org_idx)))
+            .or_else(|| <Agents<T>>::get(account)
+                .filter(|agent| agent.org_id.as_slice() == org_id)
+                .map(|mut agent| {
+                    agent.role_ids = agent.role_ids.iter()
+                        .filter_map(|role_id| Self::legacy_role(role_id))
+                        .filter_map(|name| Self::find_role_id(org_id, &name))
+                        .collect();
+                    agent
+                }))
+    }
+
+    /// Lists the organizations an account acts for, in order of membership,
+    /// followed by the one of its agent not migrated yet, if any.
+    pub fn orgs_of_agent(account: &T::AccountId) -> Vec<OrgId> {
+        let mut orgs: Vec<OrgId> = (0..Self::agent_orgs_count(account))
+            .filter_map(|i| Self::org_by_index(Self::agent_org_by_index((account.clone(), i))))
+            .map(|org| org.id)
+            .collect();
+        orgs.extend(<Agents<T>>::get(account).map(|agent| agent.org_id));
+        orgs
+    }
+
+    /// Lists the agents acting for an account, one per organization.
+    pub fn agents_of_account(account: &T::AccountId) -> Vec<Agent<T::AccountId>> {
+        Self::orgs_of_agent(account).iter()
+            .filter_map(|org_id| Self::agent_of_org(account, org_id))
+            .collect()
+    }
+
+    /// Checks whether an account is an agent of any organization.
+    pub fn is_agent(account: &T::AccountId) -> bool {
+        Self::agent_orgs_count(account) > 0 || <Agents<T>>::exists(account)
+    }
+
     /// Checks whether an account has the 'Admin' role for the specified organization.
     pub fn is_admin(account: &T::AccountId, org_id: OrgId) -> bool {
         match Self::validate_is_org_active_agent(account, org_id.clone()) {
-            Ok(_) => match Self::validate_is_agent_admin(account) {
+            Ok(_) => match Self::validate_is_agent_admin(account, &org_id) {
                 Ok(_) => true,
                 Err(_) => false
             }
@@ -230,81 +601,291 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// Checks whether an account is an active agent of the specified organization,
+    /// holding a role that grants the permission. Admins hold every permission.
+    pub fn has_permission(account: &T::AccountId, org_id: OrgId, permission: &[u8]) -> bool {
+        Self::ensure_permission(account, org_id, permission).is_ok()
+    }
+
+    /// Lists every agent, active or not, of the specified organization.
+    pub fn agents_of_org(org_id: &[u8]) -> Vec<Agent<T::AccountId>> {
+        let org_idx = match Self::find_org_index(org_id) {
+            Some(org_idx) => org_idx,
+            None => return vec!()
+        };
+        (0..Self::org_agents_count(org_idx))
+            .map(|i| Self::org_agent_by_index((org_idx, i)))
+            .filter_map(|account| Self::agent_of_org(&account, org_id))
+            .collect()
+    }
+
+    /// Resolves the roles held by an agent within an organization, skipping deleted ones.
+    pub fn roles_of_agent(account: &T::AccountId, org_id: &[u8]) -> Vec<Role> {
+        match Self::agent_of_org(account, org_id) {
+            Some(agent) => agent.role_ids.iter()
+                .filter_map(|role_id| <OrgRoles<T>>::get(role_id))
+                .collect(),
+            None => vec!()
+        }
+    }
+
+    /// Same as `has_permission`, but reports why the permission check failed.
+    pub fn ensure_permission(account: &T::AccountId, org_id: OrgId, permission: &[u8]) -> Result {
+        Self::validate_is_org_active_agent(account, org_id.clone())?;
+        let agent = Self::agent_of_org(account, &org_id).ok_or(ERR_SENDER_MUST_BE_ORG_AGENT)?;
+        if Self::has_admin_role(&agent) {
+            return Ok(());
+        }
+
+        // Deleted roles no longer resolve, so they don't grant anything
+        let granted = agent.role_ids.iter()
+            .filter_map(|role_id| <OrgRoles<T>>::get(role_id))
+            .filter(|role| role.org_id == agent.org_id)
+            .any(|role| role.permissions.iter().any(|p| p.as_slice() == permission));
+        ensure!(granted, ERR_SENDER_MISSING_PERMISSION);
+        Ok(())
+    }
+
+    /// Returns the index of an organization, unless it's still in the legacy layout.
+    pub fn find_org_index(id: &[u8]) -> Option<u32> {
+        if <OrgsIndex<T>>::exists(id.to_vec()) {
+            Some(Self::org_index(id.to_vec()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns an organization by ID.
+    /// Organizations not migrated yet are looked up in the legacy layout.
+    pub fn org_by_id(id: &[u8]) -> Option<Organization> {
+        if <OrgsIndex<T>>::exists(id.to_vec()) {
+            Self::org_by_index(Self::org_index(id.to_vec()))
+        } else {
+            Self::legacy_org(id.to_vec())
+        }
+    }
+
     // Helpers
     pub fn validate_new_org(id: &[u8]) -> Result {
-        ensure!(
-            !<Organizations<T>>::exists::<Vec<u8>>(id.into()),
-            ERR_ORG_ALREADY_EXISTS
-        );
+        ensure!(Self::org_by_id(id).is_none(), ERR_ORG_ALREADY_EXISTS);
         Ok(())
     }
 
 	pub fn validate_existing_org(id: &[u8]) -> Result {
-		ensure!(<Organizations<T>>::exists::<Vec<u8>>(id.into()), ERR_ORG_DOES_NOT_EXIST);
+		ensure!(Self::org_by_id(id).is_some(), ERR_ORG_DOES_NOT_EXIST);
 		Ok(())
 	}
 
-    pub fn validate_new_agent(agent: &T::AccountId) -> Result {
-        ensure!(!<Agents<T>>::exists(agent), ERR_AGENT_ALREADY_EXISTS);
+    /// Returns the ID of the organization referenced by an index elsewhere in the storage.
+    pub fn validate_org_index(org_idx: u32) -> rstd::result::Result<OrgId, &'static str> {
+        Self::org_by_index(org_idx).map(|org| org.id).ok_or(ERR_ORG_DOES_NOT_EXIST)
+    }
+
+    pub fn validate_new_agent(account: &T::AccountId, org_id: &[u8]) -> Result {
+        ensure!(Self::agent_of_org(account, org_id).is_none(), ERR_AGENT_ALREADY_EXISTS);
+        Ok(())
+    }
+
+    pub fn validate_new_role(org_id: &[u8], name: &[u8]) -> Result {
+        ensure!(Self::find_role_id(org_id, name).is_none(), ERR_ROLE_ALREADY_EXISTS);
         Ok(())
     }
 
+    pub fn validate_existing_role(org_id: &[u8], name: &[u8])
+        -> rstd::result::Result<u32, &'static str> {
+        Self::find_role_id(org_id, name).ok_or(ERR_ROLE_DOES_NOT_EXIST)
+    }
+
+    // Roles are only defined by organizations of the indexed layout
+    fn find_role_id(org_id: &[u8], name: &[u8]) -> Option<u32> {
+        let key = (Self::find_org_index(org_id)?, name.to_vec());
+        if <OrgRolesIndex<T>>::exists(&key) {
+            Some(Self::role_index(&key))
+        } else {
+            None
+        }
+    }
+
+    pub fn validate_existing_org_agent(account: &T::AccountId, org_id: &[u8])
+        -> rstd::result::Result<Agent<T::AccountId>, &'static str> {
+        match Self::agent_of_org(account, org_id) {
+            Some(agent) => Ok(agent),
+            None if Self::is_agent(account) => fail!(ERR_AGENT_NOT_IN_ORG),
+            None => fail!(ERR_AGENT_DOES_NOT_EXIST)
+        }
+    }
+
     pub fn validate_is_org_active_agent(account: &T::AccountId, org_id: OrgId) -> Result {
-		match <Agents<T>>::get(account) {
+		match Self::agent_of_org(account, &org_id) {
             Some(agent) => {
-				if agent.org_id != org_id {
-					fail!(ERR_SENDER_MUST_BE_ORG_AGENT);
-				}
 				if !agent.active {
 					fail!(ERR_SENDER_MUST_BE_ACTIVE_ADMIN);
 				}
 				Ok(())
             },
+            None if Self::is_agent(account) => fail!(ERR_SENDER_MUST_BE_ORG_AGENT),
             None => fail!(ERR_SENDER_IS_NOT_AN_AGENT)
         }
     }
 
-    pub fn validate_is_agent_admin(account: &T::AccountId) -> Result {
-        let admin_role_id = <RolesIndex<T>>::get(ROLE_ADMIN.to_vec());
-		match <Agents<T>>::get(account) {
+    pub fn validate_is_agent_admin(account: &T::AccountId, org_id: &[u8]) -> Result {
+		match Self::agent_of_org(account, org_id) {
             Some(agent) => {
-				if !agent.role_ids.contains(&admin_role_id) {
+				if !Self::has_admin_role(&agent) {
 					fail!(ERR_SENDER_MUST_BE_ORG_ADMIN);
 				}
 				Ok(())
             },
+            None if Self::is_agent(account) => fail!(ERR_SENDER_MUST_BE_ORG_AGENT),
             None => fail!(ERR_SENDER_IS_NOT_AN_AGENT)
         }
     }
 
-    // PRIVATE MUTABLES
-    fn get_or_add_roles(roles: Vec<Role>) -> rstd::result::Result<Vec<u32>, &'static str> {
+    fn has_admin_role(agent: &Agent<T::AccountId>) -> bool {
+        Self::find_role_id(&agent.org_id, ROLE_ADMIN)
+            .map_or(false, |admin_role_id| agent.role_ids.contains(&admin_role_id))
+    }
+
+    fn contains_admin_role(roles: &[RoleName]) -> bool {
+        roles.iter().any(|role| role.as_slice() == &ROLE_ADMIN[..])
+    }
+
+    /// Resolves role names to the IDs of roles defined by the organization.
+    fn get_role_ids(org_id: &[u8], roles: Vec<RoleName>) -> rstd::result::Result<Vec<u32>, &'static str> {
         let mut role_ids: Vec<u32> = vec!();
         for role in roles {
-            match Self::get_or_add_role_id(role) {
-                Ok(role_id) => role_ids.push(role_id),
-                Err(err) => fail!(err)
-            }
+            role_ids.push(Self::validate_existing_role(org_id, &role)?);
         }
         Ok(role_ids)
     }
 
-    fn get_or_add_role_id(role: Role) -> rstd::result::Result<u32, &'static str> {
-        match <RolesIndex<T>>::exists(&role) {
-            true => Ok(<RolesIndex<T>>::get(&role)),
-            false => {
-                let roles_count = Self::roles_count();
-                let new_role_idx = roles_count.checked_add(1)
-                    .ok_or("Overflow adding a new role")?;
-                
-                <Roles<T>>::insert(new_role_idx, role.clone());
-                <RolesCount<T>>::put(new_role_idx);
-                <RolesIndex<T>>::insert(role, new_role_idx);
+    // PRIVATE MUTABLES
 
-                Ok(new_role_idx)
-            }
+    /// Validates & stores a new organization, along with its admin role & agent.
+    fn add_org(id: OrgId, name: OrgName, admin: T::AccountId) -> Result {
+        let org = OrganizationBuilder::default()
+            .with_id(id.clone())
+            .with_name(name)
+            .build()?;
+        Self::validate_new_org(&id)?;
+
+        let mut agent = AgentBuilder::<T::AccountId>::default()
+            .with_org(id.clone())
+            .with_account(admin.clone())
+            .is_active(true)
+            .build()?;
+        Self::validate_new_agent(&admin, &id)?;
+
+        // Do this after all valitadions cause we're potentially mutating state
+        let org_idx = Self::insert_org(org)?;
+        let admin_role_id = Self::get_or_add_role(&id, ROLE_ADMIN.to_vec())?;
+        agent.role_ids = vec![admin_role_id];
+
+        Self::add_org_agent(org_idx, &admin)?;
+        Self::insert_agent(agent)?;
+        Self::increment_active_admins(org_idx)
+    }
+
+    /// Returns the index of an existing organization, moving it from the legacy layout if needed.
+    /// Call it after all validations, as it potentially mutates state.
+    pub fn index_org(id: &[u8]) -> rstd::result::Result<u32, &'static str> {
+        if let Some(org_idx) = Self::find_org_index(id) {
+            return Ok(org_idx);
+        }
+        let org = Self::legacy_org(id.to_vec()).ok_or(ERR_ORG_DOES_NOT_EXIST)?;
+        Self::insert_org(org)
+    }
+
+    /// Stores the agent of an account within an organization.
+    /// A legacy agent of the same organization is migrated along the way.
+    fn insert_agent(agent: Agent<T::AccountId>) -> Result {
+        let org_idx = Self::index_org(&agent.org_id)?;
+        let key = (agent.account.clone(), org_idx);
+        if !<Memberships<T>>::exists(&key) {
+            Self::add_agent_org(&agent.account, org_idx)?;
+        }
+        if <Agents<T>>::get(&agent.account).map_or(false, |legacy| legacy.org_id == agent.org_id) {
+            <Agents<T>>::remove(&agent.account);
+        }
+        <Memberships<T>>::insert(key, agent);
+        Ok(())
+    }
+
+    fn add_agent_org(account: &T::AccountId, org_idx: u32) -> Result {
+        let count = Self::agent_orgs_count(account);
+        let new_count = count.checked_add(1)
+            .ok_or("Overflow adding a new agent organization")?;
+
+        <IndexedAgentOrgs<T>>::insert((account.clone(), count), org_idx);
+        <AgentOrgsCount<T>>::insert(account, new_count);
+        Ok(())
+    }
+
+    fn add_org_agent(org_idx: u32, account: &T::AccountId) -> Result {
+        let count = Self::org_agents_count(org_idx);
+        let new_count = count.checked_add(1)
+            .ok_or("Overflow adding a new agent")?;
+
+        <OrgAgents<T>>::insert((org_idx, count), account);
+        <OrgAgentsCount<T>>::insert(org_idx, new_count);
+        Ok(())
+    }
+
+    fn increment_active_admins(org_idx: u32) -> Result {
+        let count = Self::active_admins_count(org_idx)
+            .checked_add(1)
+            .ok_or("Overflow adding a new admin")?;
+        <ActiveAdminsCount<T>>::insert(org_idx, count);
+        Ok(())
+    }
+
+    fn decrement_active_admins(org_idx: u32) {
+        let count = Self::active_admins_count(org_idx).saturating_sub(1);
+        <ActiveAdminsCount<T>>::insert(org_idx, count);
+    }
+
+    // Stores an organization under its index, allocating one if it's new or still in the legacy layout
+    fn insert_org(org: Organization) -> rstd::result::Result<u32, &'static str> {
+        if let Some(org_idx) = Self::find_org_index(&org.id) {
+            <Orgs<T>>::insert(org_idx, org);
+            return Ok(org_idx);
+        }
+
+        let new_org_idx = Self::orgs_count().checked_add(1)
+            .ok_or("Overflow adding a new organization")?;
+        let is_legacy = <Organizations<T>>::exists(&org.id);
+        <OrgsIndex<T>>::insert(&org.id, new_org_idx);
+        <OrgsCount<T>>::put(new_org_idx);
+        <Organizations<T>>::remove(&org.id);
+        if is_legacy {
+            // Legacy admins hold the global admin role, that now resolves within their organization
+            Self::get_or_add_role(&org.id, ROLE_ADMIN.to_vec())?;
+        }
+        <Orgs<T>>::insert(new_org_idx, org);
+        Ok(new_org_idx)
+    }
+
+    /// Returns the ID of a role of the organization, adding it without permissions if it's not defined.
+    /// Names are taken as-is, as they may come from global roles, which were never validated.
+    fn get_or_add_role(org_id: &[u8], name: RoleName) -> rstd::result::Result<u32, &'static str> {
+        match Self::find_role_id(org_id, &name) {
+            Some(role_id) => Ok(role_id),
+            None => Self::add_role(Role { org_id: org_id.to_vec(), name, ..Default::default() })
         }
     }
+
+    fn add_role(role: Role) -> rstd::result::Result<u32, &'static str> {
+        let org_idx = Self::index_org(&role.org_id)?;
+        let roles_count = Self::roles_count();
+        let new_role_idx = roles_count.checked_add(1)
+            .ok_or("Overflow adding a new role")?;
+
+        <OrgRolesIndex<T>>::insert((org_idx, role.name.clone()), new_role_idx);
+        <OrgRoles<T>>::insert(new_role_idx, role);
+        <RolesCount<T>>::put(new_role_idx);
+
+        Ok(new_role_idx)
+    }
 }
 
 #[cfg(test)]
@@ -351,19 +932,50 @@ pub mod tests {
             .build_storage()
             .unwrap()
             .0;
-        //FIXME: doesn't work, see add_extra_genesis above
-        // t.extend(GenesisConfig::<GridPikeTest> {
-        // 	orgs: vec![ (String::from(TEST_EXISTING_ORG).into_bytes(), String::from(TEST_EXISTING_ORG).into_bytes()) ],
-        // }.build_storage().unwrap().0);
+        t.into()
+    }
+
+    fn build_ext_with_genesis(config: GenesisConfig<GridPikeTest>) -> runtime_io::TestExternalities<Blake2Hasher> {
+        let mut t = system::GenesisConfig::<GridPikeTest>::default()
+            .build_storage()
+            .unwrap()
+            .0;
+        t.extend(config.build_storage().unwrap().0);
         t.into()
     }
 
     const TEST_ORG_ID: &str = "did:example:123456789abcdefghijk";
     const TEST_ORG_NAME: &str = "Parity Tech";
+    const TEST_NEW_ORG_NAME: &str = "Parity Technologies";
     const TEST_EXISTING_ORG: &str = "did:example:azertyuiop";
+    const TEST_ROLE: &[u8] = b"operator";
+    const TEST_ROLE_DESCRIPTION: &[u8] = b"Operates the production line";
+    const TEST_PERMISSION: &[u8] = b"schema::can-create-schema";
     const LONG_VALUE : &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Donec aliquam ut tortor nec congue. Pellente";
 
+	/// Returns the index of an organization, allocated on first use, so that its roles & agents
+	/// can be stored before the organization itself.
+	pub fn test_org_index(id: &[u8]) -> u32 {
+		if <OrgsIndex<GridPikeTest>>::exists(id.to_vec()) {
+			return <OrgsIndex<GridPikeTest>>::get(id.to_vec());
+		}
+		let new_org_idx = <OrgsCount<GridPikeTest>>::get() + 1;
+		<OrgsIndex<GridPikeTest>>::insert(id.to_vec(), new_org_idx);
+		<OrgsCount<GridPikeTest>>::put(new_org_idx);
+		new_org_idx
+	}
+
 	pub fn store_test_org(id: OrgId, name: OrgName) {
+		<Orgs<GridPikeTest>>::insert(
+			test_org_index(&id),
+			Organization {
+				id: id,
+				name: name,
+			},
+		);
+	}
+
+	pub fn store_legacy_org(id: OrgId, name: OrgName) {
 		Organizations::<GridPikeTest>::insert(
 			id.clone(),
 			Organization {
@@ -376,31 +988,112 @@ pub mod tests {
 	pub fn store_test_agent(
 		account: u64, org_id: OrgId,
 		active: bool, role_ids: Vec<u32>) {
+		let org_idx = test_org_index(&org_id);
+		let admin_role = (org_idx, ROLE_ADMIN.to_vec());
+		if active && <OrgRolesIndex<GridPikeTest>>::exists(&admin_role)
+			&& role_ids.contains(&<OrgRolesIndex<GridPikeTest>>::get(&admin_role)) {
+			<ActiveAdminsCount<GridPikeTest>>::mutate(org_idx, |count| *count += 1);
+		}
+		let count = <OrgAgentsCount<GridPikeTest>>::get(org_idx);
+		<OrgAgents<GridPikeTest>>::insert((org_idx, count), account);
+		<OrgAgentsCount<GridPikeTest>>::insert(org_idx, count + 1);
+		let orgs_count = <AgentOrgsCount<GridPikeTest>>::get(&account);
+		<IndexedAgentOrgs<GridPikeTest>>::insert((account, orgs_count), org_idx);
+		<AgentOrgsCount<GridPikeTest>>::insert(&account, orgs_count + 1);
+		Memberships::<GridPikeTest>::insert(
+			(account, org_idx),
+			Agent {
+				org_id: org_id,
+				account: account,
+				active: active,
+				role_ids: role_ids,
+			},
+		);
+	}
+
+	/// Stores an agent in the single-organization layout, holding global roles, as before the migration.
+	fn store_legacy_agent(account: u64, org_id: OrgId, role_ids: Vec<u32>) {
 		Agents::<GridPikeTest>::insert(
 			&account,
 			Agent {
 				org_id: org_id,
 				account: account,
-				active: active,
+				active: true,
 				role_ids: role_ids,
 			},
 		);
 	}
 
-    pub fn store_admin_role() -> u32 {
-        let admin_role = ROLE_ADMIN.to_vec();
+	/// Stores a global role, as before roles were scoped to organizations.
+	fn store_legacy_role(name: RoleName) -> u32 {
+		let new_role_idx = <RolesCount<GridPikeTest>>::get() + 1;
+		<Roles<GridPikeTest>>::insert(new_role_idx, name);
+		<RolesCount<GridPikeTest>>::put(new_role_idx);
+		new_role_idx
+	}
+
+    pub fn store_admin_role(org_id: OrgId) -> u32 {
+        store_test_role(org_id, ROLE_ADMIN.to_vec(), vec!())
+    }
 
+    pub fn store_test_role(org_id: OrgId, name: RoleName, permissions: Vec<Permission>) -> u32 {
         let roles_count = <RolesCount<GridPikeTest>>::get();
         let new_role_idx = roles_count.checked_add(1)
             .expect("Overflow adding a new role");
-        
-        <Roles<GridPikeTest>>::insert(new_role_idx, admin_role.clone());
+
+        <OrgRoles<GridPikeTest>>::insert(
+            new_role_idx,
+            Role {
+                org_id: org_id.clone(),
+                name: name.clone(),
+                description: vec!(),
+                permissions: permissions,
+            },
+        );
         <RolesCount<GridPikeTest>>::put(new_role_idx);
-        <RolesIndex<GridPikeTest>>::insert(admin_role, new_role_idx);
+        <OrgRolesIndex<GridPikeTest>>::insert((test_org_index(&org_id), name), new_role_idx);
 
         new_role_idx
     }
 
+    // genesis config tests
+    #[test]
+    fn genesis_config_with_orgs_and_roles() {
+        let admin = 1;
+        let id = String::from(TEST_ORG_ID).into_bytes();
+        let name = String::from(TEST_ORG_NAME).into_bytes();
+        let config = GenesisConfig::<GridPikeTest> {
+            orgs: vec![(id.clone(), name.clone(), admin)],
+            roles: vec![(
+                id.clone(), TEST_ROLE.to_vec(),
+                TEST_ROLE_DESCRIPTION.to_vec(), vec![TEST_PERMISSION.to_vec()])],
+        };
+
+        with_externalities(&mut build_ext_with_genesis(config), || {
+            assert_eq!(
+                GridPike::org_by_id(&id),
+                Some(Organization {
+                    id: id.clone(),
+                    name: name
+                })
+            );
+            assert_eq!(GridPike::is_admin(&admin, id.clone()), true);
+            assert_eq!(GridPike::active_admins_count(test_org_index(&id)), 1);
+            assert_eq!(GridPike::org_agents_count(test_org_index(&id)), 1);
+
+            let role_id = OrgRolesIndex::<GridPikeTest>::get((test_org_index(&id), TEST_ROLE.to_vec()));
+            assert_eq!(
+                GridPike::role_by_index(role_id),
+                Some(Role {
+                    org_id: id,
+                    name: TEST_ROLE.to_vec(),
+                    description: TEST_ROLE_DESCRIPTION.to_vec(),
+                    permissions: vec![TEST_PERMISSION.to_vec()]
+                })
+            );
+        })
+    }
+
     // create_org tests
     #[test]
     fn create_org_with_valid_args() {
@@ -425,9 +1118,17 @@ pub mod tests {
                 })
             );
 
+            assert_eq!(GridPike::orgs_count(), 1);
+            assert_eq!(GridPike::org_index(&id), 1);
+
             assert_eq!(
-                Roles::<GridPikeTest>::get(1),
-                ROLE_ADMIN.to_vec()
+                OrgRoles::<GridPikeTest>::get(1),
+                Some(Role {
+                    org_id: id.clone(),
+                    name: ROLE_ADMIN.to_vec(),
+                    description: vec!(),
+                    permissions: vec!()
+                })
             );
 
             assert_eq!(
@@ -436,12 +1137,12 @@ pub mod tests {
             );
 
             assert_eq!(
-                RolesIndex::<GridPikeTest>::get(ROLE_ADMIN.to_vec()),
+                OrgRolesIndex::<GridPikeTest>::get((test_org_index(&id), ROLE_ADMIN.to_vec())),
                 1
             );
 
             assert_eq!(
-                GridPike::agent_by_account(&sender),
+                GridPike::agent_of_org(&sender, &id),
                 Some(Agent {
                     org_id: id.clone(),
                     account: sender,
@@ -449,6 +1150,10 @@ pub mod tests {
                     role_ids: vec![ADMIN_ROLE_ID]
                 })
             );
+
+            assert_eq!(GridPike::active_admins_count(test_org_index(&id)), 1);
+            assert_eq!(GridPike::org_agents_count(test_org_index(&id)), 1);
+            assert_eq!(GridPike::org_agent_by_index((test_org_index(&id), 0)), sender);
         })
     }
 
@@ -524,19 +1229,141 @@ pub mod tests {
     }
 
     #[test]
-    fn create_org_with_existing_agent() {
+    fn create_org_with_agent_of_other_org() {
         with_externalities(&mut build_ext(), || {
-            // Arrange
             let sender = 1;
             let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
             let name = String::from(TEST_ORG_NAME).into_bytes();
 
-			store_test_agent(
-				sender.clone(), String::from(TEST_EXISTING_ORG).into_bytes(), true, vec!());
+			store_test_org(other_org.clone(), name.clone());
+			store_test_agent(sender.clone(), other_org.clone(), false, vec!());
+
+            assert_ok!(GridPike::create_org(Origin::signed(sender), id.clone(), name));
+
+            // Each membership keeps its own active flag & roles
+            assert_eq!(GridPike::orgs_of_agent(&sender), vec![other_org.clone(), id.clone()]);
+            assert_eq!(GridPike::is_admin(&sender, id), true);
+            assert_eq!(GridPike::is_admin(&sender, other_org.clone()), false);
+            assert_eq!(GridPike::agent_of_org(&sender, &other_org).map(|a| a.active), Some(false));
+        })
+    }
+
+    // update_org tests
+    #[test]
+    fn update_org_with_valid_args() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let new_name = String::from(TEST_NEW_ORG_NAME).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            let result = GridPike::update_org(Origin::signed(admin), id.clone(), new_name.clone());
+
+            assert_ok!(result);
+
+            assert_eq!(
+                GridPike::org_by_id(&id),
+                Some(Organization {
+                    id: id.clone(),
+                    name: new_name
+                })
+            );
+        })
+    }
 
+    #[test]
+    fn update_org_with_missing_name() {
+        with_externalities(&mut build_ext(), || {
             assert_noop!(
-                GridPike::create_org(Origin::signed(sender), id, name),
-                ERR_AGENT_ALREADY_EXISTS
+                GridPike::update_org(
+                    Origin::signed(1),
+                    String::from(TEST_ORG_ID).into_bytes(),
+                    vec!()
+                ),
+                ERR_ORG_NAME_REQUIRED
+            );
+        })
+    }
+
+    #[test]
+    fn update_org_with_unknown_org() {
+        with_externalities(&mut build_ext(), || {
+            assert_noop!(
+                GridPike::update_org(
+                    Origin::signed(1),
+                    String::from(TEST_ORG_ID).into_bytes(),
+                    String::from(TEST_NEW_ORG_NAME).into_bytes()
+                ),
+                ERR_ORG_DOES_NOT_EXIST
+            );
+        })
+    }
+
+    #[test]
+    fn update_org_with_invalid_sender() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+			let org_name = String::from(TEST_ORG_NAME).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), org_name.clone());
+			store_test_org(other_org.clone(), org_name.clone());
+			store_test_agent(admin, other_org, true, vec![admin_role_id]);
+
+            assert_noop!(
+                GridPike::update_org(
+                    Origin::signed(admin),
+                    id,
+                    String::from(TEST_NEW_ORG_NAME).into_bytes()
+                ),
+                ERR_SENDER_MUST_BE_ORG_AGENT
+            );
+        })
+    }
+
+    #[test]
+    fn update_org_with_non_admin_account() {
+        with_externalities(&mut build_ext(), || {
+            let non_admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(non_admin, id.clone(), true, vec!());
+
+            assert_noop!(
+                GridPike::update_org(
+                    Origin::signed(non_admin),
+                    id,
+                    String::from(TEST_NEW_ORG_NAME).into_bytes()
+                ),
+                ERR_SENDER_MUST_BE_ORG_ADMIN
+            );
+        })
+    }
+
+    #[test]
+    fn update_org_with_inactive_admin_account() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), false, vec![admin_role_id]);
+
+            assert_noop!(
+                GridPike::update_org(
+                    Origin::signed(admin),
+                    id,
+                    String::from(TEST_NEW_ORG_NAME).into_bytes()
+                ),
+                ERR_SENDER_MUST_BE_ACTIVE_ADMIN
             );
         })
     }
@@ -550,10 +1377,11 @@ pub mod tests {
             let agent = 2;
             let id = String::from(TEST_ORG_ID).into_bytes();
 
-            // Create org & admin agent
-            let _admin_role_id = store_admin_role();
+            // Create org, operator role & admin agent
+            let admin_role_id = store_admin_role(id.clone());
+            let operator_role_id = store_test_role(id.clone(), ROLE_OPERATOR.to_vec(), vec!());
 			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
-			store_test_agent(admin.clone(), id.clone(), true, vec![1]);
+			store_test_agent(admin.clone(), id.clone(), true, vec![admin_role_id]);
 
             // Send tx to create non-admin agent for org
             let result =
@@ -562,28 +1390,35 @@ pub mod tests {
             assert_ok!(result);
 
             assert_eq!(
-                GridPike::agent_by_account(&agent),
+                GridPike::agent_of_org(&agent, &id),
                 Some(Agent {
                     org_id: id.clone(),
                     account: agent,
                     active: true,
-                    role_ids: vec!(2)
+                    role_ids: vec![operator_role_id]
                 })
             );
+            assert_eq!(GridPike::org_agents_count(test_org_index(&id)), 2);
+            assert_eq!(GridPike::org_agent_by_index((test_org_index(&id), 1)), agent);
+        })
+    }
 
-            assert_eq!(
-                Roles::<GridPikeTest>::get(2),
-                ROLE_OPERATOR.to_vec()
-            );
+    #[test]
+    fn create_agent_with_unknown_role() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
 
-            assert_eq!(
-                RolesCount::<GridPikeTest>::get(),
-                2
-            );
+            // Role exists, but is defined by another org
+            let admin_role_id = store_admin_role(id.clone());
+            store_test_role(other_org, b"operator".to_vec(), vec!());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
 
-            assert_eq!(
-                RolesIndex::<GridPikeTest>::get(ROLE_OPERATOR.to_vec()),
-                2
+            assert_noop!(
+                GridPike::create_agent(Origin::signed(admin), id, 2, true, vec![b"operator".to_vec()]),
+                ERR_ROLE_DOES_NOT_EXIST
             );
         })
     }
@@ -672,6 +1507,41 @@ pub mod tests {
         })
     }
 
+    #[test]
+    fn create_agent_with_agent_of_other_org() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let agent = 2;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+            let role_id = store_test_role(id.clone(), TEST_ROLE.to_vec(), vec!());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+			let other_admin_role_id = store_admin_role(other_org.clone());
+			store_test_org(other_org.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, other_org.clone(), true, vec![other_admin_role_id]);
+
+            assert_ok!(GridPike::create_agent(
+                Origin::signed(admin), id.clone(), agent, false, vec![TEST_ROLE.to_vec()]));
+
+            assert_eq!(
+                GridPike::agents_of_account(&agent),
+                vec![
+                    Agent { org_id: other_org.clone(), account: agent, active: true, role_ids: vec![other_admin_role_id] },
+                    Agent { org_id: id.clone(), account: agent, active: false, role_ids: vec![role_id] },
+                ]
+            );
+            // Still admin of the other org only
+            assert_eq!(GridPike::is_admin(&agent, other_org), true);
+            assert_noop!(
+                GridPike::create_role(Origin::signed(agent), id, b"auditor".to_vec(), vec!(), vec!()),
+                ERR_SENDER_MUST_BE_ACTIVE_ADMIN
+            );
+        })
+    }
+
 	#[test]
 	fn create_agent_with_invalid_sender() {
 		with_externalities(&mut build_ext(), || {
@@ -728,7 +1598,7 @@ pub mod tests {
 			let agent = 2;
             let id = String::from(TEST_ORG_ID).into_bytes();
 
-            let admin_role_id = store_admin_role();
+            let admin_role_id = store_admin_role(id.clone());
 			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
 			store_test_agent(admin.clone(), id.clone(), false, vec![admin_role_id]);
 
@@ -745,78 +1615,844 @@ pub mod tests {
         })
     }
 
-    // is_admin tests
+    // update_agent tests
     #[test]
-    fn is_admin_for_actual_org_admin() {
+    fn update_agent_with_valid_args() {
         with_externalities(&mut build_ext(), || {
-            let agent = 1;
-            let org_id = String::from(TEST_ORG_ID).into_bytes();
+            const ROLE_OPERATOR: &[u8] = b"operator";
+            let admin = 1;
+            let agent = 2;
+            let id = String::from(TEST_ORG_ID).into_bytes();
 
-			// Insert test data directly into storage to test public immutable func
-            let admin_role_id = store_admin_role();
-			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
-			store_test_agent(agent, org_id.clone(), true, vec![admin_role_id]);
+            let admin_role_id = store_admin_role(id.clone());
+            let operator_role_id = store_test_role(id.clone(), ROLE_OPERATOR.to_vec(), vec!());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+			store_test_agent(agent, id.clone(), true, vec!());
 
-            // Test is_admin method
-            assert_eq!(GridPike::is_admin(&agent, org_id), true);
+            let result = GridPike::update_agent(
+                Origin::signed(admin), id.clone(), agent, false, vec![ROLE_OPERATOR.to_vec()]);
+
+            assert_ok!(result);
+
+            assert_eq!(
+                GridPike::agent_of_org(&agent, &id),
+                Some(Agent {
+                    org_id: id.clone(),
+                    account: agent,
+                    active: false,
+                    role_ids: vec![operator_role_id]
+                })
+            );
         })
     }
 
     #[test]
-    fn is_admin_for_unknown_agent() {
+    fn update_agent_promotes_admin() {
         with_externalities(&mut build_ext(), || {
-            let agent = 1;
-            let org_id = String::from(TEST_ORG_ID).into_bytes();
+            let admin = 1;
+            let agent = 2;
+            let id = String::from(TEST_ORG_ID).into_bytes();
 
-            assert_eq!(GridPike::is_admin(&agent, org_id), false);
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+			store_test_agent(agent, id.clone(), true, vec!());
+
+            assert_ok!(GridPike::update_agent(
+                Origin::signed(admin), id.clone(), agent, true, vec![ROLE_ADMIN.to_vec()]));
+
+            assert_eq!(GridPike::is_admin(&agent, id.clone()), true);
+            assert_eq!(GridPike::active_admins_count(test_org_index(&id)), 2);
+
+            // The former admin can now be demoted by the new one
+            assert_ok!(GridPike::update_agent(
+                Origin::signed(agent), id.clone(), admin, true, vec!()));
+
+            assert_eq!(GridPike::is_admin(&admin, id.clone()), false);
+            assert_eq!(GridPike::active_admins_count(test_org_index(&id)), 1);
         })
     }
 
     #[test]
-    fn is_admin_for_inactive_org_agent() {
+    fn update_agent_deactivating_last_admin() {
         with_externalities(&mut build_ext(), || {
-            let agent = 1;
-            let org_id = String::from(TEST_ORG_ID).into_bytes();
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
 
-			// Insert test data directly into storage to test public immutable func
-            let admin_role_id = store_admin_role();
-			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
-			store_test_agent(agent, org_id.clone(), false, vec![admin_role_id]); // <-- /!\ Agent is inactive
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
 
-            assert_eq!(GridPike::is_admin(&agent, org_id), false);
+            assert_noop!(
+                GridPike::update_agent(
+                    Origin::signed(admin), id.clone(), admin, false, vec![ROLE_ADMIN.to_vec()]),
+                ERR_ORG_MUST_KEEP_ACTIVE_ADMIN
+            );
         })
     }
 
     #[test]
-    fn is_admin_for_non_admin_org_agent() {
+    fn update_agent_removing_last_admin_role() {
         with_externalities(&mut build_ext(), || {
-            let agent = 1;
-            let org_id = String::from(TEST_ORG_ID).into_bytes();
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
 
-			// Insert test data directly into storage to test public immutable func
-			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
-			store_test_agent(agent, org_id.clone(), true, vec!()); // <-- /!\ Agent is not admin
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
 
-            assert_eq!(GridPike::is_admin(&agent, org_id), false);
+            assert_noop!(
+                GridPike::update_agent(Origin::signed(admin), id.clone(), admin, true, vec!()),
+                ERR_ORG_MUST_KEEP_ACTIVE_ADMIN
+            );
         })
     }
 
     #[test]
-    fn is_admin_for_agent_org_mismatch() {
+    fn update_agent_with_unknown_agent() {
         with_externalities(&mut build_ext(), || {
-            let agent = 1;
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            assert_noop!(
+                GridPike::update_agent(Origin::signed(admin), id, 2, true, vec!()),
+                ERR_AGENT_DOES_NOT_EXIST
+            );
+        })
+    }
+
+    #[test]
+    fn update_agent_of_another_org() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let agent = 2;
             let id = String::from(TEST_ORG_ID).into_bytes();
             let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+			let org_name = String::from(TEST_ORG_NAME).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), org_name.clone());
+			store_test_org(other_org.clone(), org_name.clone());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+			store_test_agent(agent, other_org, true, vec!());
+
+            assert_noop!(
+                GridPike::update_agent(Origin::signed(admin), id, agent, false, vec!()),
+                ERR_AGENT_NOT_IN_ORG
+            );
+        })
+    }
+
+    #[test]
+    fn update_agent_with_non_admin_account() {
+        with_externalities(&mut build_ext(), || {
+            let non_admin = 1;
+            let agent = 2;
+            let id = String::from(TEST_ORG_ID).into_bytes();
 
-			// Insert test data directly into storage to test public immutable func
-            let admin_role_id = store_admin_role();
 			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
-			store_test_agent(agent, id.clone(), true, vec![admin_role_id]);
+			store_test_agent(non_admin, id.clone(), true, vec!());
+			store_test_agent(agent, id.clone(), true, vec!());
+
+            assert_noop!(
+                GridPike::update_agent(Origin::signed(non_admin), id, agent, false, vec!()),
+                ERR_SENDER_MUST_BE_ORG_ADMIN
+            );
+        })
+    }
 
+    // create_role tests
+    #[test]
+    fn create_role_with_valid_args() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            let result = GridPike::create_role(
+                Origin::signed(admin),
+                id.clone(),
+                TEST_ROLE.to_vec(),
+                TEST_ROLE_DESCRIPTION.to_vec(),
+                vec![TEST_PERMISSION.to_vec()]
+            );
+
+            assert_ok!(result);
+
+            let role_id = OrgRolesIndex::<GridPikeTest>::get((test_org_index(&id), TEST_ROLE.to_vec()));
             assert_eq!(
-                GridPike::is_admin(&agent, other_org), // <-- /!\ Agent is admin for another org
-                false
+                GridPike::role_by_index(role_id),
+                Some(Role {
+                    org_id: id,
+                    name: TEST_ROLE.to_vec(),
+                    description: TEST_ROLE_DESCRIPTION.to_vec(),
+                    permissions: vec![TEST_PERMISSION.to_vec()]
+                })
+            );
+        })
+    }
+
+    #[test]
+    fn create_role_with_same_name_in_other_org() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+            let other_role_id = store_test_role(other_org.clone(), TEST_ROLE.to_vec(), vec!());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            assert_ok!(GridPike::create_role(
+                Origin::signed(admin), id.clone(), TEST_ROLE.to_vec(), vec!(), vec![TEST_PERMISSION.to_vec()]));
+
+            assert!(OrgRolesIndex::<GridPikeTest>::get((test_org_index(&id), TEST_ROLE.to_vec())) != other_role_id);
+            assert_eq!(GridPike::role_by_index(other_role_id).unwrap().permissions, Vec::<Permission>::new());
+        })
+    }
+
+    #[test]
+    fn create_role_with_missing_name() {
+        with_externalities(&mut build_ext(), || {
+            assert_noop!(
+                GridPike::create_role(
+                    Origin::signed(1), String::from(TEST_ORG_ID).into_bytes(), vec!(), vec!(), vec!()),
+                ERR_ROLE_NAME_REQUIRED
             );
         })
     }
+
+    #[test]
+    fn create_role_with_duplicate_permissions() {
+        with_externalities(&mut build_ext(), || {
+            assert_noop!(
+                GridPike::create_role(
+                    Origin::signed(1),
+                    String::from(TEST_ORG_ID).into_bytes(),
+                    TEST_ROLE.to_vec(),
+                    vec!(),
+                    vec![TEST_PERMISSION.to_vec(), TEST_PERMISSION.to_vec()]
+                ),
+                ERR_PERMISSION_DUPLICATED
+            );
+        })
+    }
+
+    #[test]
+    fn create_role_with_existing_name() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            assert_noop!(
+                GridPike::create_role(Origin::signed(admin), id, ROLE_ADMIN.to_vec(), vec!(), vec!()),
+                ERR_ROLE_ALREADY_EXISTS
+            );
+        })
+    }
+
+    #[test]
+    fn create_role_with_non_admin_account() {
+        with_externalities(&mut build_ext(), || {
+            let non_admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(non_admin, id.clone(), true, vec!());
+
+            assert_noop!(
+                GridPike::create_role(Origin::signed(non_admin), id, TEST_ROLE.to_vec(), vec!(), vec!()),
+                ERR_SENDER_MUST_BE_ORG_ADMIN
+            );
+        })
+    }
+
+    // update_role tests
+    #[test]
+    fn update_role_with_valid_args() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+            let role_id = store_test_role(id.clone(), TEST_ROLE.to_vec(), vec!());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            assert_ok!(GridPike::update_role(
+                Origin::signed(admin),
+                id.clone(),
+                TEST_ROLE.to_vec(),
+                TEST_ROLE_DESCRIPTION.to_vec(),
+                vec![TEST_PERMISSION.to_vec()]
+            ));
+
+            assert_eq!(
+                GridPike::role_by_index(role_id),
+                Some(Role {
+                    org_id: id,
+                    name: TEST_ROLE.to_vec(),
+                    description: TEST_ROLE_DESCRIPTION.to_vec(),
+                    permissions: vec![TEST_PERMISSION.to_vec()]
+                })
+            );
+        })
+    }
+
+    #[test]
+    fn update_role_with_unknown_role() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            assert_noop!(
+                GridPike::update_role(Origin::signed(admin), id, TEST_ROLE.to_vec(), vec!(), vec!()),
+                ERR_ROLE_DOES_NOT_EXIST
+            );
+        })
+    }
+
+    #[test]
+    fn update_role_for_admin_role() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            assert_noop!(
+                GridPike::update_role(Origin::signed(admin), id, ROLE_ADMIN.to_vec(), vec!(), vec!()),
+                ERR_ROLE_IS_RESERVED
+            );
+        })
+    }
+
+    // delete_role tests
+    #[test]
+    fn delete_role_with_valid_args() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+            let role_id = store_test_role(id.clone(), TEST_ROLE.to_vec(), vec!());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            assert_ok!(GridPike::delete_role(Origin::signed(admin), id.clone(), TEST_ROLE.to_vec()));
+
+            assert_eq!(GridPike::role_by_index(role_id), None);
+            assert_eq!(OrgRolesIndex::<GridPikeTest>::exists((test_org_index(&id), TEST_ROLE.to_vec())), false);
+        })
+    }
+
+    #[test]
+    fn delete_role_for_admin_role() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+
+            assert_noop!(
+                GridPike::delete_role(Origin::signed(admin), id, ROLE_ADMIN.to_vec()),
+                ERR_ROLE_IS_RESERVED
+            );
+        })
+    }
+
+    #[test]
+    fn delete_role_with_non_admin_account() {
+        with_externalities(&mut build_ext(), || {
+            let non_admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            store_test_role(id.clone(), TEST_ROLE.to_vec(), vec!());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(non_admin, id.clone(), true, vec!());
+
+            assert_noop!(
+                GridPike::delete_role(Origin::signed(non_admin), id, TEST_ROLE.to_vec()),
+                ERR_SENDER_MUST_BE_ORG_ADMIN
+            );
+        })
+    }
+
+    // has_permission tests
+    #[test]
+    fn has_permission_for_agent_with_granting_role() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let org_id = String::from(TEST_ORG_ID).into_bytes();
+
+            let role_id = store_test_role(org_id.clone(), TEST_ROLE.to_vec(), vec![TEST_PERMISSION.to_vec()]);
+			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, org_id.clone(), true, vec![role_id]);
+
+            assert_eq!(GridPike::has_permission(&agent, org_id.clone(), TEST_PERMISSION), true);
+            assert_eq!(GridPike::has_permission(&agent, org_id, b"schema::can-update-schema"), false);
+        })
+    }
+
+    #[test]
+    fn has_permission_for_org_admin() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let org_id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(org_id.clone());
+			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, org_id.clone(), true, vec![admin_role_id]);
+
+            assert_eq!(GridPike::has_permission(&agent, org_id, TEST_PERMISSION), true);
+        })
+    }
+
+    #[test]
+    fn has_permission_for_inactive_agent() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let org_id = String::from(TEST_ORG_ID).into_bytes();
+
+            let role_id = store_test_role(org_id.clone(), TEST_ROLE.to_vec(), vec![TEST_PERMISSION.to_vec()]);
+			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, org_id.clone(), false, vec![role_id]);
+
+            assert_eq!(GridPike::has_permission(&agent, org_id, TEST_PERMISSION), false);
+        })
+    }
+
+    #[test]
+    fn has_permission_for_deleted_role() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let org_id = String::from(TEST_ORG_ID).into_bytes();
+
+            let role_id = store_test_role(org_id.clone(), TEST_ROLE.to_vec(), vec![TEST_PERMISSION.to_vec()]);
+			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, org_id.clone(), true, vec![role_id]);
+            <OrgRoles<GridPikeTest>>::remove(role_id);
+
+            assert_eq!(
+                GridPike::ensure_permission(&agent, org_id, TEST_PERMISSION),
+                Err(ERR_SENDER_MISSING_PERMISSION)
+            );
+        })
+    }
+
+    // is_admin tests
+    #[test]
+    fn is_admin_for_actual_org_admin() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let org_id = String::from(TEST_ORG_ID).into_bytes();
+
+			// Insert test data directly into storage to test public immutable func
+            let admin_role_id = store_admin_role(org_id.clone());
+			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, org_id.clone(), true, vec![admin_role_id]);
+
+            // Test is_admin method
+            assert_eq!(GridPike::is_admin(&agent, org_id), true);
+        })
+    }
+
+    #[test]
+    fn is_admin_for_unknown_agent() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let org_id = String::from(TEST_ORG_ID).into_bytes();
+
+            assert_eq!(GridPike::is_admin(&agent, org_id), false);
+        })
+    }
+
+    #[test]
+    fn is_admin_for_inactive_org_agent() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let org_id = String::from(TEST_ORG_ID).into_bytes();
+
+			// Insert test data directly into storage to test public immutable func
+            let admin_role_id = store_admin_role(org_id.clone());
+			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, org_id.clone(), false, vec![admin_role_id]); // <-- /!\ Agent is inactive
+
+            assert_eq!(GridPike::is_admin(&agent, org_id), false);
+        })
+    }
+
+    #[test]
+    fn is_admin_for_non_admin_org_agent() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let org_id = String::from(TEST_ORG_ID).into_bytes();
+
+			// Insert test data directly into storage to test public immutable func
+			store_test_org(org_id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, org_id.clone(), true, vec!()); // <-- /!\ Agent is not admin
+
+            assert_eq!(GridPike::is_admin(&agent, org_id), false);
+        })
+    }
+
+    #[test]
+    fn is_admin_for_agent_org_mismatch() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+
+			// Insert test data directly into storage to test public immutable func
+            let admin_role_id = store_admin_role(id.clone());
+			store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, id.clone(), true, vec![admin_role_id]);
+
+            assert_eq!(
+                GridPike::is_admin(&agent, other_org), // <-- /!\ Agent is admin for another org
+                false
+            );
+        })
+    }
+
+    #[test]
+    fn agents_of_org_lists_org_agents_only() {
+        with_externalities(&mut build_ext(), || {
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+
+            store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+            store_test_org(other_org.clone(), String::from(TEST_ORG_NAME).into_bytes());
+            store_test_agent(1, id.clone(), true, vec!());
+            store_test_agent(2, other_org, true, vec!());
+            store_test_agent(3, id.clone(), false, vec!());
+
+            let accounts: Vec<u64> = GridPike::agents_of_org(&id).iter().map(|a| a.account).collect();
+            assert_eq!(accounts, vec![1, 3]);
+            assert_eq!(GridPike::agents_of_org(b"did:example:unknown"), vec!());
+        })
+    }
+
+    #[test]
+    fn roles_of_agent_skips_deleted_roles() {
+        with_externalities(&mut build_ext(), || {
+            let agent = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+            let role_id = store_test_role(id.clone(), TEST_ROLE.to_vec(), vec![TEST_PERMISSION.to_vec()]);
+            store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+            store_test_agent(agent, id.clone(), true, vec![admin_role_id, role_id]);
+            assert_ok!(GridPike::delete_role(Origin::signed(agent), id.clone(), TEST_ROLE.to_vec()));
+
+            assert_eq!(
+                GridPike::roles_of_agent(&agent, &id).iter().map(|r| r.name.clone()).collect::<Vec<_>>(),
+                vec![ROLE_ADMIN.to_vec()]
+            );
+            assert_eq!(GridPike::roles_of_agent(&2, &id), vec!());
+            assert_eq!(GridPike::roles_of_agent(&agent, TEST_EXISTING_ORG.as_bytes()), vec!());
+        })
+    }
+
+    // migrate_agents tests
+    #[test]
+    fn legacy_agent_acts_until_migrated() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let legacy_admin_role_id = store_legacy_role(ROLE_ADMIN.to_vec());
+            let admin_role_id = store_admin_role(id.clone());
+            store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+            store_legacy_agent(admin, id.clone(), vec![legacy_admin_role_id]);
+
+            assert_eq!(GridPike::is_admin(&admin, id.clone()), true);
+            assert_eq!(GridPike::orgs_of_agent(&admin), vec![id.clone()]);
+            assert_eq!(
+                GridPike::agents_of_account(&admin),
+                vec![Agent { org_id: id.clone(), account: admin, active: true, role_ids: vec![admin_role_id] }]
+            );
+            assert_ok!(GridPike::create_role(
+                Origin::signed(admin), id, TEST_ROLE.to_vec(), vec!(), vec!()));
+        })
+    }
+
+    #[test]
+    fn migrate_agents_moves_legacy_agents() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let agent = 2;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let legacy_admin_role_id = store_legacy_role(ROLE_ADMIN.to_vec());
+            let admin_role_id = store_admin_role(id.clone());
+            store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+            store_legacy_agent(admin, id.clone(), vec![legacy_admin_role_id]);
+            store_legacy_agent(agent, id.clone(), vec!());
+
+            // Unknown accounts are skipped
+            assert_ok!(GridPike::migrate_agents(Origin::ROOT, vec![admin, agent, 3]));
+
+            assert_eq!(GridPike::legacy_agent(&admin), None);
+            assert_eq!(GridPike::legacy_agent(&agent), None);
+            assert_eq!(GridPike::orgs_of_agent(&admin), vec![id.clone()]);
+            assert_eq!(
+                GridPike::agent_of_org(&agent, &id),
+                Some(Agent { org_id: id.clone(), account: agent, active: true, role_ids: vec!() })
+            );
+            assert_eq!(GridPike::agent_of_org(&admin, &id).unwrap().role_ids, vec![admin_role_id]);
+            assert_eq!(GridPike::is_admin(&admin, id.clone()), true);
+
+            // Replaying a batch is harmless
+            assert_ok!(GridPike::migrate_agents(Origin::ROOT, vec![admin]));
+            assert_eq!(GridPike::orgs_of_agent(&admin), vec![id]);
+        })
+    }
+
+    #[test]
+    fn migrate_orgs_and_agents_with_global_roles() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let operator = 2;
+            let other_admin = 3;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+            let name = String::from(TEST_ORG_NAME).into_bytes();
+
+            let legacy_admin_role_id = store_legacy_role(ROLE_ADMIN.to_vec());
+            let legacy_operator_role_id = store_legacy_role(TEST_ROLE.to_vec());
+            store_legacy_org(id.clone(), name.clone());
+            store_legacy_org(other_org.clone(), name.clone());
+            store_legacy_agent(admin, id.clone(), vec![legacy_admin_role_id]);
+            store_legacy_agent(operator, id.clone(), vec![legacy_operator_role_id]);
+            store_legacy_agent(other_admin, other_org.clone(), vec![legacy_admin_role_id]);
+
+            // Admin roles are only defined once organizations are migrated
+            assert_eq!(GridPike::is_admin(&admin, id.clone()), false);
+
+            assert_ok!(GridPike::migrate_orgs(Origin::ROOT, vec![id.clone(), other_org.clone()]));
+
+            assert_eq!(GridPike::is_admin(&admin, id.clone()), true);
+            assert_eq!(GridPike::is_admin(&other_admin, other_org.clone()), true);
+            assert_eq!(GridPike::is_admin(&admin, other_org.clone()), false);
+            let admin_role_id = GridPike::role_index((test_org_index(&id), ROLE_ADMIN.to_vec()));
+            assert_ne!(admin_role_id, legacy_admin_role_id);
+            assert_ne!(admin_role_id, GridPike::role_index((test_org_index(&other_org), ROLE_ADMIN.to_vec())));
+
+            assert_ok!(GridPike::migrate_agents(Origin::ROOT, vec![admin, operator, other_admin]));
+            assert_ok!(GridPike::backfill_org_agents(Origin::ROOT, id.clone(), vec![admin, operator]));
+
+            assert_eq!(GridPike::legacy_agent(&admin), None);
+            assert_eq!(GridPike::agent_of_org(&admin, &id).unwrap().role_ids, vec![admin_role_id]);
+            assert_eq!(
+                GridPike::roles_of_agent(&operator, &id),
+                vec![Role { org_id: id.clone(), name: TEST_ROLE.to_vec(), ..Default::default() }]
+            );
+            assert_eq!(GridPike::is_admin(&other_admin, other_org.clone()), true);
+            assert_eq!(GridPike::active_admins_count(test_org_index(&id)), 1);
+
+            assert_ok!(GridPike::update_org(Origin::signed(admin), id.clone(), b"Renamed org".to_vec()));
+            assert_ok!(GridPike::update_agent(
+                Origin::signed(admin), id.clone(), operator, true, vec![ROLE_ADMIN.to_vec()]));
+            assert_eq!(GridPike::active_admins_count(test_org_index(&id)), 2);
+        })
+    }
+
+    #[test]
+    fn migrate_agents_requires_root() {
+        with_externalities(&mut build_ext(), || {
+            assert_noop!(
+                GridPike::migrate_agents(Origin::signed(1), vec![1]),
+                "bad origin: expected to be a root origin"
+            );
+        })
+    }
+
+    #[test]
+    fn update_agent_migrates_legacy_agent() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let agent = 2;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+            store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+            store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+            store_legacy_agent(agent, id.clone(), vec!());
+
+            assert_ok!(GridPike::update_agent(Origin::signed(admin), id.clone(), agent, false, vec!()));
+
+            assert_eq!(GridPike::legacy_agent(&agent), None);
+            assert_eq!(GridPike::agent_of_org(&agent, &id).map(|a| a.active), Some(false));
+            assert_eq!(GridPike::orgs_of_agent(&agent), vec![id]);
+        })
+    }
+
+    // backfill_org_agents tests
+
+    /// Stores an agent as created before agents were indexed by organization.
+    fn store_unindexed_agent(account: u64, org_id: OrgId, active: bool, role_ids: Vec<u32>) {
+        let orgs_count = <AgentOrgsCount<GridPikeTest>>::get(&account);
+        <IndexedAgentOrgs<GridPikeTest>>::insert((account, orgs_count), test_org_index(&org_id));
+        <AgentOrgsCount<GridPikeTest>>::insert(&account, orgs_count + 1);
+        Memberships::<GridPikeTest>::insert(
+            (account, test_org_index(&org_id)),
+            Agent {
+                org_id: org_id,
+                account: account,
+                active: active,
+                role_ids: role_ids,
+            },
+        );
+    }
+
+    #[test]
+    fn backfill_org_agents_indexes_existing_agents() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let other_admin = 2;
+            let agent = 3;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            let admin_role_id = store_admin_role(id.clone());
+            store_test_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+            store_test_agent(admin, id.clone(), true, vec![admin_role_id]);
+            store_unindexed_agent(other_admin, id.clone(), true, vec![admin_role_id]);
+            store_unindexed_agent(agent, id.clone(), true, vec!());
+            assert_eq!(GridPike::agents_of_org(&id).len(), 1);
+
+            // Indexed and unknown accounts are skipped
+            assert_ok!(GridPike::backfill_org_agents(
+                Origin::ROOT, id.clone(), vec![admin, other_admin, agent, 4, agent]));
+
+            assert_eq!(
+                GridPike::agents_of_org(&id).iter().map(|a| a.account).collect::<Vec<_>>(),
+                vec![admin, other_admin, agent]
+            );
+            assert_eq!(GridPike::active_admins_count(test_org_index(&id)), 2);
+            // Either admin can now step down
+            assert_ok!(GridPike::update_agent(Origin::signed(admin), id.clone(), other_admin, false, vec!()));
+
+            // Replaying a batch is harmless
+            assert_ok!(GridPike::backfill_org_agents(Origin::ROOT, id.clone(), vec![other_admin]));
+            assert_eq!(GridPike::org_agents_count(test_org_index(&id)), 3);
+            assert_eq!(GridPike::active_admins_count(test_org_index(&id)), 1);
+        })
+    }
+
+    #[test]
+    fn backfill_org_agents_with_invalid_args() {
+        with_externalities(&mut build_ext(), || {
+            let id = String::from(TEST_ORG_ID).into_bytes();
+
+            assert_noop!(
+                GridPike::backfill_org_agents(Origin::signed(1), id.clone(), vec![1]),
+                "bad origin: expected to be a root origin"
+            );
+            assert_noop!(
+                GridPike::backfill_org_agents(Origin::ROOT, id, vec![1]),
+                ERR_ORG_DOES_NOT_EXIST
+            );
+        })
+    }
+
+    // migrate_orgs tests
+    #[test]
+    fn legacy_org_found_until_migrated() {
+        with_externalities(&mut build_ext(), || {
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let name = String::from(TEST_ORG_NAME).into_bytes();
+
+            store_legacy_org(id.clone(), name.clone());
+
+            assert_eq!(GridPike::org_by_id(&id), Some(Organization { id: id.clone(), name: name.clone() }));
+            assert_noop!(
+                GridPike::create_org(Origin::signed(1), id, name),
+                ERR_ORG_ALREADY_EXISTS
+            );
+        })
+    }
+
+    #[test]
+    fn migrate_orgs_moves_legacy_orgs() {
+        with_externalities(&mut build_ext(), || {
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+            let name = String::from(TEST_ORG_NAME).into_bytes();
+
+            store_test_org(other_org.clone(), name.clone());
+            store_legacy_org(id.clone(), name.clone());
+
+            // Unknown and already indexed organizations are skipped
+            assert_ok!(GridPike::migrate_orgs(
+                Origin::ROOT, vec![id.clone(), other_org.clone(), b"did:example:unknown".to_vec()]));
+
+            assert_eq!(GridPike::legacy_org(&id), None);
+            assert_eq!(GridPike::orgs_count(), 2);
+            assert_eq!(GridPike::org_index(&other_org), 1);
+            assert_eq!(GridPike::org_index(&id), 2);
+            assert_eq!(GridPike::org_by_index(2), Some(Organization { id: id.clone(), name: name }));
+
+            // Replaying a batch is harmless
+            assert_ok!(GridPike::migrate_orgs(Origin::ROOT, vec![id.clone()]));
+            assert_eq!(GridPike::orgs_count(), 2);
+            assert_eq!(GridPike::org_index(&id), 2);
+        })
+    }
+
+    #[test]
+    fn migrate_orgs_requires_root() {
+        with_externalities(&mut build_ext(), || {
+            assert_noop!(
+                GridPike::migrate_orgs(Origin::signed(1), vec![TEST_ORG_ID.as_bytes().to_vec()]),
+                "bad origin: expected to be a root origin"
+            );
+        })
+    }
+
+    #[test]
+    fn update_legacy_org_once_migrated() {
+        with_externalities(&mut build_ext(), || {
+            let admin = 1;
+            let id = String::from(TEST_ORG_ID).into_bytes();
+            let new_name = b"Renamed org".to_vec();
+
+            let legacy_admin_role_id = store_legacy_role(ROLE_ADMIN.to_vec());
+            store_legacy_org(id.clone(), String::from(TEST_ORG_NAME).into_bytes());
+            store_legacy_agent(admin, id.clone(), vec![legacy_admin_role_id]);
+
+            // The admin role is only defined once the organization is indexed
+            assert_noop!(
+                GridPike::update_org(Origin::signed(admin), id.clone(), new_name.clone()),
+                ERR_SENDER_MUST_BE_ORG_ADMIN
+            );
+
+            assert_ok!(GridPike::migrate_orgs(Origin::ROOT, vec![id.clone()]));
+            assert_ok!(GridPike::update_org(Origin::signed(admin), id.clone(), new_name.clone()));
+
+            assert_eq!(GridPike::org_index(&id), 1);
+            assert_eq!(GridPike::org_by_id(&id), Some(Organization { id: id, name: new_name }));
+        })
+    }
 }
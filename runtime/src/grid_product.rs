@@ -0,0 +1,542 @@
+// Copyright 2019 Steve Degosserie
+// Hyperledger Grid Product compatible runtime module
+
+use crate::grid_gs1::{validate_gs1_key, ensure_can_create, ensure_can_update};
+use crate::grid_pike::{OrgId, validate_org_id};
+use crate::grid_pike::Module as PikeModule;
+use crate::grid_schema::PropertyValue;
+use crate::grid_schema::Trait as SchemaTrait;
+use crate::grid_schema::Module as SchemaModule;
+use rstd::prelude::*;
+use parity_codec::{Decode, Encode};
+use support::{
+	decl_module, decl_storage, decl_event,
+	ensure, StorageMap,
+	dispatch::Result
+};
+use system::ensure_signed;
+
+const ERR_PRODUCT_ID_REQUIRED: &str = "Product ID required";
+const ERR_PRODUCT_ID_INVALID_GTIN: &str = "Product ID must be a valid GS1 GTIN";
+const ERR_PRODUCT_ALREADY_EXISTS: &str = "Product already exists";
+const ERR_PRODUCT_DOES_NOT_EXIST: &str = "Product does not exist";
+
+// GTIN-8, GTIN-12, GTIN-13 & GTIN-14
+const GTIN_LENGTHS: [usize; 4] = [8, 12, 13, 14];
+
+pub const PERM_CAN_CREATE_PRODUCT: &[u8] = b"product::can-create-product";
+pub const PERM_CAN_UPDATE_PRODUCT: &[u8] = b"product::can-update-product";
+pub const PERM_CAN_DELETE_PRODUCT: &[u8] = b"product::can-delete-product";
+
+// Schema that product properties are validated against
+pub const PRODUCT_SCHEMA: &[u8] = b"gs1_product";
+
+pub type ProductId = Vec<u8>;
+
+pub fn validate_gtin(id: &[u8]) -> Result {
+	validate_gs1_key(id, &GTIN_LENGTHS, ERR_PRODUCT_ID_REQUIRED, ERR_PRODUCT_ID_INVALID_GTIN)
+}
+
+pub fn validate_product_id(product_namespace: &ProductNamespace, product_id: &[u8]) -> Result {
+	match product_namespace {
+		ProductNamespace::GS1 => validate_gtin(product_id),
+	}
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum ProductNamespace {
+	GS1,
+}
+
+impl Default for ProductNamespace {
+	fn default() -> Self {
+		ProductNamespace::GS1
+	}
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct Product {
+	pub product_id: ProductId,
+	pub product_namespace: ProductNamespace,
+	// Index of the owner organization
+	pub owner: u32,
+	pub properties: Vec<PropertyValue>,
+}
+
+#[derive(Default)]
+pub struct ProductBuilder {
+	product_id: ProductId,
+	product_namespace: ProductNamespace,
+	owner: u32,
+	properties: Vec<PropertyValue>,
+}
+impl ProductBuilder {
+	pub fn with_id(mut self, product_id: ProductId) -> Self {
+		self.product_id = product_id;
+		self
+	}
+
+	pub fn with_namespace(mut self, product_namespace: ProductNamespace) -> Self {
+		self.product_namespace = product_namespace;
+		self
+	}
+
+	pub fn with_owner(mut self, owner: u32) -> Self {
+		self.owner = owner;
+		self
+	}
+
+	pub fn with_properties(mut self, properties: Vec<PropertyValue>) -> Self {
+		self.properties = properties;
+		self
+	}
+
+	pub fn build(self) -> rstd::result::Result<Product, &'static str> {
+		validate_product_id(&self.product_namespace, &self.product_id)?;
+		let mut product = Product::default();
+		product.product_id = self.product_id;
+		product.product_namespace = self.product_namespace;
+		product.owner = self.owner;
+		product.properties = self.properties;
+		Ok(product)
+	}
+}
+
+pub trait Trait: SchemaTrait {
+	type Event: From<Event> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as GridProduct {
+		Products get(product_by_id): map ProductId => Option<Product>;
+	}
+}
+
+decl_event!(
+	pub enum Event {
+		ProductCreated(ProductId, OrgId),
+		ProductUpdated(ProductId, OrgId),
+		ProductDeleted(ProductId, OrgId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		pub fn create_product(
+			origin, product_id: ProductId, product_namespace: ProductNamespace,
+			owner: OrgId, properties: Vec<PropertyValue>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			validate_product_id(&product_namespace, &product_id)?;
+			validate_org_id(&owner)?;
+			Self::validate_new_product(&product_id)?;
+			ensure_can_create::<T>(&sender, &owner, PERM_CAN_CREATE_PRODUCT, PRODUCT_SCHEMA, &properties)?;
+
+			let product = ProductBuilder::default()
+				.with_id(product_id.clone())
+				.with_namespace(product_namespace)
+				.with_owner(<PikeModule<T>>::index_org(&owner)?)
+				.with_properties(properties)
+				.build()?;
+
+			<Products<T>>::insert(&product_id, product);
+			<SchemaModule<T>>::warn_if_deprecated(PRODUCT_SCHEMA);
+
+			Self::deposit_event(Event::ProductCreated(product_id, owner));
+
+			Ok(())
+		}
+
+		pub fn update_product(
+			origin, product_id: ProductId,
+			properties: Vec<PropertyValue>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let existing = Self::validate_existing_product(&product_id)?;
+			let owner = <PikeModule<T>>::validate_org_index(existing.owner)?;
+			let product = ProductBuilder::default()
+				.with_id(product_id.clone())
+				.with_namespace(existing.product_namespace)
+				.with_owner(existing.owner)
+				.with_properties(properties)
+				.build()?;
+			ensure_can_update::<T>(&sender, &owner, PERM_CAN_UPDATE_PRODUCT, PRODUCT_SCHEMA, &product.properties)?;
+
+			<Products<T>>::insert(&product_id, product);
+			<SchemaModule<T>>::warn_if_deprecated(PRODUCT_SCHEMA);
+
+			Self::deposit_event(Event::ProductUpdated(product_id, owner));
+
+			Ok(())
+		}
+
+		pub fn delete_product(origin, product_id: ProductId) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let product = Self::validate_existing_product(&product_id)?;
+			let owner = <PikeModule<T>>::validate_org_index(product.owner)?;
+
+			// Validate signer is an active agent of the owner org,
+			// with the right to delete product
+			<PikeModule<T>>::ensure_permission(&sender, owner.clone(), PERM_CAN_DELETE_PRODUCT)?;
+
+			<Products<T>>::remove(&product_id);
+
+			Self::deposit_event(Event::ProductDeleted(product_id, owner));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+
+	// Helpers
+	fn validate_new_product(product_id: &[u8]) -> Result {
+		ensure!(!<Products<T>>::exists(product_id.to_vec()), ERR_PRODUCT_ALREADY_EXISTS);
+		Ok(())
+	}
+
+	fn validate_existing_product(product_id: &[u8]) -> rstd::result::Result<Product, &'static str> {
+		<Products<T>>::get(product_id.to_vec()).ok_or(ERR_PRODUCT_DOES_NOT_EXIST)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::grid_pike::{
+		ERR_ORG_DOES_NOT_EXIST, ERR_SENDER_MUST_BE_ORG_AGENT, ERR_SENDER_MISSING_PERMISSION};
+	use crate::grid_gs1::tests::{self as gs1, org, store_test_editor, store_other_admin};
+	use crate::grid_pike::Trait as PikeTrait;
+	use crate::grid_pike::tests::test_org_index;
+	use crate::grid_schema::{
+		PropertyDefinition, DataType, SchemaStatus, Value, ERR_PROPERTY_VALUE_REQUIRED, ERR_SCHEMA_RETIRED};
+	use crate::grid_schema::tests::set_test_schema_status;
+	use crate::{grid_pike, grid_schema, grid_product};
+
+	use primitives::{Blake2Hasher, H256};
+	use runtime_io::with_externalities;
+	use runtime_primitives::{
+		testing::{Digest, DigestItem, Header},
+		traits::{BlakeTwo256, IdentityLookup},
+		BuildStorage,
+	};
+	use support::{assert_noop, assert_ok, impl_outer_event, impl_outer_origin};
+
+	impl_outer_origin! {
+		pub enum Origin for GridProductTest {}
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for GridProductTest {
+			grid_pike<T>, grid_schema, grid_product,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct GridProductTest;
+
+	impl system::Trait for GridProductTest {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type Digest = Digest;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = TestEvent;
+		type Log = DigestItem;
+	}
+	impl PikeTrait for GridProductTest {
+		type Event = TestEvent;
+	}
+	impl SchemaTrait for GridProductTest {
+		type Event = TestEvent;
+	}
+	impl Trait for GridProductTest {
+		type Event = TestEvent;
+	}
+
+	type GridProduct = super::Module<GridProductTest>;
+	type System = system::Module<GridProductTest>;
+
+	fn build_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let t = system::GenesisConfig::<GridProductTest>::default()
+			.build_storage()
+			.unwrap()
+			.0;
+		t.into()
+	}
+
+	const TEST_GTIN: &[u8] = b"4006381333931";
+
+	fn deprecated_schema_used() -> bool {
+		let warning = TestEvent::grid_schema(grid_schema::Event::DeprecatedSchemaUsed(PRODUCT_SCHEMA.to_vec()));
+		System::events().into_iter().any(|record| record.event == warning)
+	}
+
+	fn product_name(name: &[u8]) -> Vec<PropertyValue> {
+		vec![PropertyValue { name: b"product_name".to_vec(), value: Value::String(name.to_vec()) }]
+	}
+
+	fn test_properties() -> Vec<PropertyValue> {
+		product_name(b"Ballpoint pen")
+	}
+
+	fn store_test_admin(admin: u64) -> OrgId {
+		gs1::store_test_admin(
+			admin,
+			PRODUCT_SCHEMA,
+			vec![
+				PropertyDefinition { name: b"product_name".to_vec(), data_type: DataType::String, required: true, ..Default::default() },
+			]
+		)
+	}
+
+	fn store_test_product(owner: OrgId) {
+		Products::<GridProductTest>::insert(
+			TEST_GTIN.to_vec(),
+			Product {
+				product_id: TEST_GTIN.to_vec(),
+				product_namespace: ProductNamespace::GS1,
+				owner: test_org_index(&owner),
+				properties: test_properties(),
+			},
+		);
+	}
+
+	// create_product tests
+	#[test]
+	fn create_product_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+
+			let result = GridProduct::create_product(
+				Origin::signed(sender), TEST_GTIN.to_vec(), ProductNamespace::GS1,
+				owner.clone(), test_properties());
+
+			assert_ok!(result);
+
+			assert_eq!(
+				GridProduct::product_by_id(TEST_GTIN.to_vec()),
+				Some(Product {
+					product_id: TEST_GTIN.to_vec(),
+					product_namespace: ProductNamespace::GS1,
+					owner: test_org_index(&owner),
+					properties: test_properties(),
+				})
+			);
+		})
+	}
+
+	#[test]
+	fn create_product_with_missing_id() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridProduct::create_product(
+					Origin::signed(1), vec!(), ProductNamespace::GS1,
+					org(), vec!()),
+				ERR_PRODUCT_ID_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_product_with_invalid_gtin() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridProduct::create_product(
+					Origin::signed(1), b"4006381333932".to_vec(), ProductNamespace::GS1,
+					org(), vec!()),
+				ERR_PRODUCT_ID_INVALID_GTIN
+			);
+		})
+	}
+
+	#[test]
+	fn create_product_with_existing_id() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			store_test_product(owner.clone());
+
+			assert_noop!(
+				GridProduct::create_product(
+					Origin::signed(sender), TEST_GTIN.to_vec(), ProductNamespace::GS1, owner, vec!()),
+				ERR_PRODUCT_ALREADY_EXISTS
+			);
+		})
+	}
+
+	#[test]
+	fn create_product_with_unknown_org() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridProduct::create_product(
+					Origin::signed(1), TEST_GTIN.to_vec(), ProductNamespace::GS1,
+					org(), vec!()),
+				ERR_ORG_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn create_product_without_permission() {
+		with_externalities(&mut build_ext(), || {
+			let agent = 1;
+			let owner = store_test_editor(agent, PERM_CAN_UPDATE_PRODUCT);
+
+			assert_noop!(
+				GridProduct::create_product(
+					Origin::signed(agent), TEST_GTIN.to_vec(), ProductNamespace::GS1, owner, vec!()),
+				ERR_SENDER_MISSING_PERMISSION
+			);
+		})
+	}
+
+	#[test]
+	fn create_product_with_invalid_properties() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+
+			assert_noop!(
+				GridProduct::create_product(
+					Origin::signed(sender), TEST_GTIN.to_vec(), ProductNamespace::GS1, owner, vec!()),
+				ERR_PROPERTY_VALUE_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_product_with_retired_schema() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			set_test_schema_status(PRODUCT_SCHEMA, SchemaStatus::Retired);
+
+			assert_noop!(
+				GridProduct::create_product(
+					Origin::signed(sender), TEST_GTIN.to_vec(), ProductNamespace::GS1, owner, test_properties()),
+				ERR_SCHEMA_RETIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_product_with_deprecated_schema() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			set_test_schema_status(PRODUCT_SCHEMA, SchemaStatus::Deprecated);
+			assert!(!deprecated_schema_used());
+
+			assert_ok!(GridProduct::create_product(
+				Origin::signed(sender), TEST_GTIN.to_vec(), ProductNamespace::GS1, owner, test_properties()));
+			assert!(GridProduct::product_by_id(TEST_GTIN.to_vec()).is_some());
+			assert!(deprecated_schema_used());
+		})
+	}
+
+	// update_product tests
+	#[test]
+	fn update_product_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			store_test_product(owner.clone());
+
+			let properties = product_name(b"Fountain pen");
+			assert_ok!(GridProduct::update_product(
+				Origin::signed(sender), TEST_GTIN.to_vec(), properties.clone()));
+
+			assert_eq!(
+				GridProduct::product_by_id(TEST_GTIN.to_vec()).map(|p| p.properties),
+				Some(properties)
+			);
+			assert!(!deprecated_schema_used());
+		})
+	}
+
+	#[test]
+	fn update_product_with_deprecated_schema() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			store_test_product(owner);
+			set_test_schema_status(PRODUCT_SCHEMA, SchemaStatus::Deprecated);
+
+			let properties = product_name(b"Fountain pen");
+			assert_ok!(GridProduct::update_product(
+				Origin::signed(sender), TEST_GTIN.to_vec(), properties.clone()));
+			assert_eq!(
+				GridProduct::product_by_id(TEST_GTIN.to_vec()).map(|p| p.properties),
+				Some(properties)
+			);
+			assert!(deprecated_schema_used());
+		})
+	}
+
+	#[test]
+	fn update_product_with_unknown_product() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridProduct::update_product(Origin::signed(1), TEST_GTIN.to_vec(), vec!()),
+				ERR_PRODUCT_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn update_product_from_other_org() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let other = 2;
+			let owner = store_test_admin(sender);
+			store_test_product(owner);
+
+			store_other_admin(other);
+
+			assert_noop!(
+				GridProduct::update_product(Origin::signed(other), TEST_GTIN.to_vec(), vec!()),
+				ERR_SENDER_MUST_BE_ORG_AGENT
+			);
+		})
+	}
+
+	// delete_product tests
+	#[test]
+	fn delete_product_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = store_test_admin(sender);
+			store_test_product(owner);
+
+			assert_ok!(GridProduct::delete_product(Origin::signed(sender), TEST_GTIN.to_vec()));
+
+			assert_eq!(GridProduct::product_by_id(TEST_GTIN.to_vec()), None);
+		})
+	}
+
+	#[test]
+	fn delete_product_without_permission() {
+		with_externalities(&mut build_ext(), || {
+			let agent = 1;
+			let owner = store_test_editor(agent, PERM_CAN_UPDATE_PRODUCT);
+			store_test_product(owner);
+
+			assert_noop!(
+				GridProduct::delete_product(Origin::signed(agent), TEST_GTIN.to_vec()),
+				ERR_SENDER_MISSING_PERMISSION
+			);
+		})
+	}
+}
@@ -0,0 +1,732 @@
+// Copyright 2019 Steve Degosserie
+// Hyperledger Grid Purchase Order compatible runtime module
+
+use crate::grid_pike::{OrgId, BYTEARRAY_LIMIT, validate_org_id};
+use crate::grid_pike::Trait as PikeTrait;
+use crate::grid_pike::Module as PikeModule;
+use crate::grid_offchain::{OffchainRef, validate_offchain_ref};
+use rstd::prelude::*;
+use parity_codec::{Decode, Encode};
+use support::{
+	decl_module, decl_storage, decl_event,
+	ensure, StorageMap,
+	dispatch::Result
+};
+use system::ensure_signed;
+
+pub const ERR_PO_ID_REQUIRED: &str = "Purchase order ID required";
+pub const ERR_PO_ID_TOO_LONG: &str = "Purchase order ID too long";
+pub const ERR_PO_ALREADY_EXISTS: &str = "Purchase order already exists";
+pub const ERR_PO_DOES_NOT_EXIST: &str = "Purchase order does not exist";
+pub const ERR_PO_BUYER_IS_SELLER: &str = "Purchase order buyer and seller must be different organizations";
+pub const ERR_PO_IS_COMPLETED: &str = "Purchase order is closed or cancelled";
+pub const ERR_PO_NOT_ISSUED: &str = "Purchase order versions can only change until the order is confirmed";
+pub const ERR_PO_VERSION_DOES_NOT_EXIST: &str = "Purchase order version does not exist";
+pub const ERR_PO_VERSION_NOT_DRAFT: &str = "Purchase order version is not a draft";
+pub const ERR_PO_VERSION_NOT_ACCEPTED: &str = "Purchase order has no accepted version";
+pub const ERR_ORG_MUST_BE_BUYER_OR_SELLER: &str = "Organization must be the purchase order buyer or seller";
+pub const ERR_ORG_MUST_BE_COUNTERPARTY: &str = "Version must be accepted by the other party";
+pub const ERR_INVALID_WORKFLOW_TRANSITION: &str = "Invalid purchase order workflow transition";
+
+pub const PERM_CAN_CREATE_PO: &[u8] = b"po::can-create-po";
+pub const PERM_CAN_UPDATE_PO: &[u8] = b"po::can-update-po";
+
+pub const INITIAL_PO_VERSION: u32 = 1;
+
+pub type PoId = Vec<u8>;
+
+fn validate_po_id(po_id: &[u8]) -> Result {
+	ensure!(po_id.len() > 0, ERR_PO_ID_REQUIRED);
+	ensure!(po_id.len() <= BYTEARRAY_LIMIT, ERR_PO_ID_TOO_LONG);
+	Ok(())
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum WorkflowState {
+	Issued,
+	Confirmed,
+	Closed,
+	Cancelled,
+}
+
+impl Default for WorkflowState {
+	fn default() -> Self {
+		WorkflowState::Issued
+	}
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum VersionStatus {
+	Draft,
+	Accepted,
+	// Accepted before another version was
+	Superseded,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct PurchaseOrder {
+	pub po_id: PoId,
+	// Indexes of the buyer & seller organizations
+	pub buyer_org: u32,
+	pub seller_org: u32,
+	pub workflow_state: WorkflowState,
+	// Number of versions, stored in PurchaseOrderVersions
+	pub versions_count: u32,
+	pub accepted_version: Option<u32>,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct PurchaseOrderVersion {
+	pub version: u32,
+	// Index of the organization that submitted the version, the other party accepts it
+	pub submitter_org: u32,
+	pub status: VersionStatus,
+	// The order document, kept off-chain
+	pub document: OffchainRef,
+}
+
+#[derive(Default)]
+pub struct PurchaseOrderBuilder {
+	po_id: PoId,
+	buyer_org: u32,
+	seller_org: u32,
+}
+impl PurchaseOrderBuilder {
+	pub fn with_id(mut self, po_id: PoId) -> Self {
+		self.po_id = po_id;
+		self
+	}
+
+	pub fn with_buyer(mut self, buyer_org: u32) -> Self {
+		self.buyer_org = buyer_org;
+		self
+	}
+
+	pub fn with_seller(mut self, seller_org: u32) -> Self {
+		self.seller_org = seller_org;
+		self
+	}
+
+	pub fn build(self) -> rstd::result::Result<PurchaseOrder, &'static str> {
+		validate_po_id(&self.po_id)?;
+		ensure!(self.buyer_org != self.seller_org, ERR_PO_BUYER_IS_SELLER);
+		let mut po = PurchaseOrder::default();
+		po.po_id = self.po_id;
+		po.buyer_org = self.buyer_org;
+		po.seller_org = self.seller_org;
+		po.workflow_state = WorkflowState::Issued;
+		Ok(po)
+	}
+}
+
+pub trait Trait: PikeTrait {
+	type Event: From<Event> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as GridPurchaseOrder {
+		PurchaseOrders get(purchase_order_by_id): map PoId => Option<PurchaseOrder>;
+		PurchaseOrderVersions get(purchase_order_version): map (PoId, u32) => Option<PurchaseOrderVersion>;
+	}
+}
+
+decl_event!(
+	pub enum Event {
+		PurchaseOrderCreated(PoId, OrgId, OrgId),
+		VersionCreated(PoId, u32, OrgId),
+		VersionAccepted(PoId, u32, OrgId),
+		WorkflowStateChanged(PoId, WorkflowState),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Issues a purchase order on behalf of the buyer org, with its document as a first draft.
+		pub fn create_po(
+			origin, po_id: PoId, buyer_org: OrgId, seller_org: OrgId,
+			document: OffchainRef) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			validate_po_id(&po_id)?;
+			validate_org_id(&buyer_org)?;
+			validate_org_id(&seller_org)?;
+			ensure!(buyer_org != seller_org, ERR_PO_BUYER_IS_SELLER);
+			Self::validate_new_po(&po_id)?;
+			validate_offchain_ref(&document)?;
+
+			// Validate orgs exist
+			<PikeModule<T>>::validate_existing_org(&buyer_org)?;
+			<PikeModule<T>>::validate_existing_org(&seller_org)?;
+			// Validate signer is an active agent of the buyer org,
+			// with the right to create purchase order
+			<PikeModule<T>>::ensure_permission(&sender, buyer_org.clone(), PERM_CAN_CREATE_PO)?;
+
+			let buyer_org_idx = <PikeModule<T>>::index_org(&buyer_org)?;
+			let mut po = PurchaseOrderBuilder::default()
+				.with_id(po_id.clone())
+				.with_buyer(buyer_org_idx)
+				.with_seller(<PikeModule<T>>::index_org(&seller_org)?)
+				.build()?;
+			Self::insert_version(&mut po, buyer_org_idx, document)?;
+			<PurchaseOrders<T>>::insert(&po_id, po);
+
+			Self::deposit_event(Event::PurchaseOrderCreated(po_id.clone(), buyer_org.clone(), seller_org));
+			Self::deposit_event(Event::VersionCreated(po_id, INITIAL_PO_VERSION, buyer_org));
+
+			Ok(())
+		}
+
+		/// Submits a new draft version of the order document, on behalf of either party.
+		pub fn create_version(
+			origin, po_id: PoId, org_id: OrgId,
+			document: OffchainRef) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let mut po = Self::validate_issued_po(&po_id)?;
+			let org_idx = Self::validate_is_party(&po, &org_id)?;
+			validate_offchain_ref(&document)?;
+			<PikeModule<T>>::ensure_permission(&sender, org_id.clone(), PERM_CAN_UPDATE_PO)?;
+
+			let version = Self::insert_version(&mut po, org_idx, document)?;
+			<PurchaseOrders<T>>::insert(&po_id, po);
+
+			Self::deposit_event(Event::VersionCreated(po_id, version, org_id));
+
+			Ok(())
+		}
+
+		/// Accepts a draft version on behalf of the party that did not submit it.
+		/// The version accepted until then, if any, is superseded.
+		pub fn accept_version(origin, po_id: PoId, org_id: OrgId, version: u32) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let mut po = Self::validate_issued_po(&po_id)?;
+			let org_idx = Self::validate_is_party(&po, &org_id)?;
+			let mut po_version = Self::purchase_order_version((po_id.clone(), version))
+				.ok_or(ERR_PO_VERSION_DOES_NOT_EXIST)?;
+			ensure!(po_version.status == VersionStatus::Draft, ERR_PO_VERSION_NOT_DRAFT);
+			ensure!(po_version.submitter_org != org_idx, ERR_ORG_MUST_BE_COUNTERPARTY);
+			<PikeModule<T>>::ensure_permission(&sender, org_id.clone(), PERM_CAN_UPDATE_PO)?;
+
+			if let Some(previous) = po.accepted_version {
+				<PurchaseOrderVersions<T>>::mutate((po_id.clone(), previous), |v| {
+					if let Some(v) = v {
+						v.status = VersionStatus::Superseded;
+					}
+				});
+			}
+			po_version.status = VersionStatus::Accepted;
+			po.accepted_version = Some(version);
+			<PurchaseOrderVersions<T>>::insert((po_id.clone(), version), po_version);
+			<PurchaseOrders<T>>::insert(&po_id, po);
+
+			Self::deposit_event(Event::VersionAccepted(po_id, version, org_id));
+
+			Ok(())
+		}
+
+		/// Advances the workflow of a purchase order, on behalf of either party.
+		pub fn update_workflow_state(
+			origin, po_id: PoId, org_id: OrgId,
+			workflow_state: WorkflowState) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let mut po = Self::validate_open_po(&po_id)?;
+			let org_idx = Self::validate_is_party(&po, &org_id)?;
+			Self::validate_transition(&po, org_idx, workflow_state)?;
+			<PikeModule<T>>::ensure_permission(&sender, org_id, PERM_CAN_UPDATE_PO)?;
+
+			po.workflow_state = workflow_state;
+			<PurchaseOrders<T>>::insert(&po_id, po);
+
+			Self::deposit_event(Event::WorkflowStateChanged(po_id, workflow_state));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+
+	// Helpers
+	fn validate_new_po(po_id: &[u8]) -> Result {
+		ensure!(!<PurchaseOrders<T>>::exists(po_id.to_vec()), ERR_PO_ALREADY_EXISTS);
+		Ok(())
+	}
+
+	fn validate_open_po(po_id: &[u8]) -> rstd::result::Result<PurchaseOrder, &'static str> {
+		let po = <PurchaseOrders<T>>::get(po_id.to_vec()).ok_or(ERR_PO_DOES_NOT_EXIST)?;
+		ensure!(
+			po.workflow_state == WorkflowState::Issued || po.workflow_state == WorkflowState::Confirmed,
+			ERR_PO_IS_COMPLETED
+		);
+		Ok(po)
+	}
+
+	/// Versions of the order document are negotiated until the seller confirms the order.
+	fn validate_issued_po(po_id: &[u8]) -> rstd::result::Result<PurchaseOrder, &'static str> {
+		let po = Self::validate_open_po(po_id)?;
+		ensure!(po.workflow_state == WorkflowState::Issued, ERR_PO_NOT_ISSUED);
+		Ok(po)
+	}
+
+	/// Returns the index of the party, both being indexed when the order is issued.
+	fn validate_is_party(po: &PurchaseOrder, org_id: &[u8]) -> rstd::result::Result<u32, &'static str> {
+		<PikeModule<T>>::find_org_index(org_id)
+			.filter(|org_idx| *org_idx == po.buyer_org || *org_idx == po.seller_org)
+			.ok_or(ERR_ORG_MUST_BE_BUYER_OR_SELLER)
+	}
+
+	/// The seller confirms an accepted order, the buyer closes it once fulfilled.
+	/// Either party can cancel it until then.
+	fn validate_transition(po: &PurchaseOrder, org_idx: u32, to: WorkflowState) -> Result {
+		let is_buyer = po.buyer_org == org_idx;
+		match (po.workflow_state, to) {
+			(WorkflowState::Issued, WorkflowState::Confirmed) if !is_buyer => {
+				ensure!(po.accepted_version.is_some(), ERR_PO_VERSION_NOT_ACCEPTED);
+				Ok(())
+			},
+			(WorkflowState::Confirmed, WorkflowState::Closed) if is_buyer => Ok(()),
+			(WorkflowState::Issued, WorkflowState::Cancelled) |
+			(WorkflowState::Confirmed, WorkflowState::Cancelled) => Ok(()),
+			_ => Err(ERR_INVALID_WORKFLOW_TRANSITION)
+		}
+	}
+
+	// PRIVATE MUTABLES
+	fn insert_version(
+		po: &mut PurchaseOrder, submitter_org: u32,
+		document: OffchainRef) -> rstd::result::Result<u32, &'static str> {
+		let version = po.versions_count.checked_add(1)
+			.ok_or("Overflow adding a new purchase order version")?;
+		po.versions_count = version;
+		<PurchaseOrderVersions<T>>::insert(
+			(po.po_id.clone(), version),
+			PurchaseOrderVersion {
+				version,
+				submitter_org,
+				status: VersionStatus::Draft,
+				document,
+			}
+		);
+		Ok(version)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::grid_pike::{ERR_ORG_DOES_NOT_EXIST, ERR_SENDER_MISSING_PERMISSION};
+	use crate::grid_cid::ERR_CID_REQUIRED;
+	use crate::grid_offchain::{HashAlgorithm, ERR_OFFCHAIN_SIZE_REQUIRED, ERR_OFFCHAIN_URI_REQUIRED};
+	use crate::grid_pike::tests::{
+		store_test_org, store_test_agent, store_admin_role, store_test_role, test_org_index};
+
+	use primitives::{Blake2Hasher, H256};
+	use runtime_io::with_externalities;
+	use runtime_primitives::{
+		testing::{Digest, DigestItem, Header},
+		traits::{BlakeTwo256, IdentityLookup},
+		BuildStorage,
+	};
+	use support::{assert_noop, assert_ok, impl_outer_origin};
+
+	impl_outer_origin! {
+		pub enum Origin for GridPurchaseOrderTest {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct GridPurchaseOrderTest;
+
+	impl system::Trait for GridPurchaseOrderTest {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type Digest = Digest;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type Log = DigestItem;
+	}
+	impl PikeTrait for GridPurchaseOrderTest {
+		type Event = ();
+	}
+	impl Trait for GridPurchaseOrderTest {
+		type Event = ();
+	}
+
+	type GridPurchaseOrder = super::Module<GridPurchaseOrderTest>;
+
+	fn build_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let t = system::GenesisConfig::<GridPurchaseOrderTest>::default()
+			.build_storage()
+			.unwrap()
+			.0;
+		t.into()
+	}
+
+	const BUYER_ORG: &[u8] = b"did:example:buyer";
+	const SELLER_ORG: &[u8] = b"did:example:seller";
+	const TEST_ORG_NAME: &str = "Parity Tech";
+	const TEST_PO_ID: &[u8] = b"PO-2019-0001";
+
+	const BUYER: u64 = 1;
+	const SELLER: u64 = 2;
+
+	fn document(seed: u8) -> OffchainRef {
+		OffchainRef::of(HashAlgorithm::Blake2_256, &[seed; 2048], None)
+	}
+
+	fn store_test_parties() {
+		for (account, org_id) in &[(BUYER, BUYER_ORG), (SELLER, SELLER_ORG)] {
+			let admin_role_id = store_admin_role(org_id.to_vec());
+			store_test_org(org_id.to_vec(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(*account, org_id.to_vec(), true, vec![admin_role_id]);
+		}
+	}
+
+	fn create_test_po() {
+		store_test_parties();
+		assert_ok!(GridPurchaseOrder::create_po(
+			Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), SELLER_ORG.to_vec(), document(1)));
+	}
+
+	fn set_state(sender: u64, org_id: &[u8], state: WorkflowState) -> Result {
+		GridPurchaseOrder::update_workflow_state(
+			Origin::signed(sender), TEST_PO_ID.to_vec(), org_id.to_vec(), state)
+	}
+
+	// create_po tests
+	#[test]
+	fn create_po_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			assert_eq!(
+				GridPurchaseOrder::purchase_order_by_id(TEST_PO_ID.to_vec()),
+				Some(PurchaseOrder {
+					po_id: TEST_PO_ID.to_vec(),
+					buyer_org: test_org_index(BUYER_ORG),
+					seller_org: test_org_index(SELLER_ORG),
+					workflow_state: WorkflowState::Issued,
+					versions_count: 1,
+					accepted_version: None,
+				})
+			);
+			assert_eq!(
+				GridPurchaseOrder::purchase_order_version((TEST_PO_ID.to_vec(), INITIAL_PO_VERSION)),
+				Some(PurchaseOrderVersion {
+					version: INITIAL_PO_VERSION,
+					submitter_org: test_org_index(BUYER_ORG),
+					status: VersionStatus::Draft,
+					document: document(1),
+				})
+			);
+		})
+	}
+
+	#[test]
+	fn create_po_with_missing_id() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridPurchaseOrder::create_po(
+					Origin::signed(BUYER), vec!(), BUYER_ORG.to_vec(), SELLER_ORG.to_vec(), document(1)),
+				ERR_PO_ID_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_po_with_invalid_document() {
+		with_externalities(&mut build_ext(), || {
+			store_test_parties();
+			let create_po = |document| GridPurchaseOrder::create_po(
+				Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), SELLER_ORG.to_vec(), document);
+
+			assert_noop!(
+				create_po(OffchainRef::of(HashAlgorithm::Blake2_256, b"", None)),
+				ERR_OFFCHAIN_SIZE_REQUIRED
+			);
+			assert_noop!(
+				create_po(OffchainRef { uri: Some(vec!()), ..document(1) }),
+				ERR_OFFCHAIN_URI_REQUIRED
+			);
+			assert_noop!(
+				create_po(OffchainRef { uri: Some(b"ipfs://".to_vec()), ..document(1) }),
+				ERR_CID_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_po_with_same_buyer_and_seller() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridPurchaseOrder::create_po(
+					Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), BUYER_ORG.to_vec(), document(1)),
+				ERR_PO_BUYER_IS_SELLER
+			);
+		})
+	}
+
+	#[test]
+	fn create_po_with_existing_id() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			assert_noop!(
+				GridPurchaseOrder::create_po(
+					Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), SELLER_ORG.to_vec(), document(2)),
+				ERR_PO_ALREADY_EXISTS
+			);
+		})
+	}
+
+	#[test]
+	fn create_po_with_unknown_seller() {
+		with_externalities(&mut build_ext(), || {
+			store_test_parties();
+
+			assert_noop!(
+				GridPurchaseOrder::create_po(
+					Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), b"did:example:unknown".to_vec(), document(1)),
+				ERR_ORG_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn create_po_without_permission() {
+		with_externalities(&mut build_ext(), || {
+			store_test_parties();
+			let clerk = 3;
+			let role_id = store_test_role(BUYER_ORG.to_vec(), b"po-clerk".to_vec(), vec![PERM_CAN_UPDATE_PO.to_vec()]);
+			store_test_agent(clerk, BUYER_ORG.to_vec(), true, vec![role_id]);
+
+			assert_noop!(
+				GridPurchaseOrder::create_po(
+					Origin::signed(clerk), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), SELLER_ORG.to_vec(), document(1)),
+				ERR_SENDER_MISSING_PERMISSION
+			);
+		})
+	}
+
+	// versions tests
+	#[test]
+	fn create_and_accept_version() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			assert_ok!(GridPurchaseOrder::create_version(
+				Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), document(2)));
+			assert_ok!(GridPurchaseOrder::accept_version(
+				Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), 2));
+
+			let po = GridPurchaseOrder::purchase_order_by_id(TEST_PO_ID.to_vec()).unwrap();
+			assert_eq!(po.versions_count, 2);
+			assert_eq!(po.accepted_version, Some(2));
+			assert_eq!(
+				GridPurchaseOrder::purchase_order_version((TEST_PO_ID.to_vec(), 2)).map(|v| v.status),
+				Some(VersionStatus::Accepted)
+			);
+			// The first draft is left untouched
+			assert_eq!(
+				GridPurchaseOrder::purchase_order_version((TEST_PO_ID.to_vec(), 1)).map(|v| v.status),
+				Some(VersionStatus::Draft)
+			);
+		})
+	}
+
+	#[test]
+	fn accept_version_supersedes_accepted_one() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+			assert_ok!(GridPurchaseOrder::accept_version(
+				Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), INITIAL_PO_VERSION));
+			assert_ok!(GridPurchaseOrder::create_version(
+				Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), document(2)));
+
+			assert_ok!(GridPurchaseOrder::accept_version(
+				Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), 2));
+
+			assert_eq!(
+				GridPurchaseOrder::purchase_order_by_id(TEST_PO_ID.to_vec()).and_then(|po| po.accepted_version),
+				Some(2)
+			);
+			assert_eq!(
+				GridPurchaseOrder::purchase_order_version((TEST_PO_ID.to_vec(), 1)).map(|v| v.status),
+				Some(VersionStatus::Superseded)
+			);
+			assert_eq!(
+				GridPurchaseOrder::purchase_order_version((TEST_PO_ID.to_vec(), 2)).map(|v| v.status),
+				Some(VersionStatus::Accepted)
+			);
+		})
+	}
+
+	#[test]
+	fn change_version_of_confirmed_po() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+			assert_ok!(GridPurchaseOrder::create_version(
+				Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), document(2)));
+			assert_ok!(GridPurchaseOrder::accept_version(
+				Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), INITIAL_PO_VERSION));
+			assert_ok!(set_state(SELLER, SELLER_ORG, WorkflowState::Confirmed));
+
+			assert_noop!(
+				GridPurchaseOrder::create_version(
+					Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), document(3)),
+				ERR_PO_NOT_ISSUED
+			);
+			assert_noop!(
+				GridPurchaseOrder::accept_version(
+					Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), 2),
+				ERR_PO_NOT_ISSUED
+			);
+		})
+	}
+
+	#[test]
+	fn create_version_with_invalid_document() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			assert_noop!(
+				GridPurchaseOrder::create_version(
+					Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(),
+					OffchainRef { uri: Some(vec!()), ..document(2) }),
+				ERR_OFFCHAIN_URI_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_version_from_other_org() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			assert_noop!(
+				GridPurchaseOrder::create_version(
+					Origin::signed(BUYER), TEST_PO_ID.to_vec(), b"did:example:carrier".to_vec(), document(2)),
+				ERR_ORG_MUST_BE_BUYER_OR_SELLER
+			);
+		})
+	}
+
+	#[test]
+	fn accept_own_version() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			assert_noop!(
+				GridPurchaseOrder::accept_version(
+					Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), INITIAL_PO_VERSION),
+				ERR_ORG_MUST_BE_COUNTERPARTY
+			);
+		})
+	}
+
+	#[test]
+	fn accept_version_twice() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+			assert_ok!(GridPurchaseOrder::accept_version(
+				Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), INITIAL_PO_VERSION));
+
+			assert_noop!(
+				GridPurchaseOrder::accept_version(
+					Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), INITIAL_PO_VERSION),
+				ERR_PO_VERSION_NOT_DRAFT
+			);
+			assert_noop!(
+				GridPurchaseOrder::accept_version(
+					Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), 5),
+				ERR_PO_VERSION_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	// workflow tests
+	#[test]
+	fn workflow_confirm_and_close() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+			assert_ok!(GridPurchaseOrder::accept_version(
+				Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), INITIAL_PO_VERSION));
+
+			assert_ok!(set_state(SELLER, SELLER_ORG, WorkflowState::Confirmed));
+			assert_ok!(set_state(BUYER, BUYER_ORG, WorkflowState::Closed));
+
+			assert_eq!(
+				GridPurchaseOrder::purchase_order_by_id(TEST_PO_ID.to_vec()).map(|po| po.workflow_state),
+				Some(WorkflowState::Closed)
+			);
+			assert_noop!(
+				GridPurchaseOrder::create_version(
+					Origin::signed(BUYER), TEST_PO_ID.to_vec(), BUYER_ORG.to_vec(), document(2)),
+				ERR_PO_IS_COMPLETED
+			);
+		})
+	}
+
+	#[test]
+	fn workflow_confirm_without_accepted_version() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			assert_noop!(
+				set_state(SELLER, SELLER_ORG, WorkflowState::Confirmed),
+				ERR_PO_VERSION_NOT_ACCEPTED
+			);
+		})
+	}
+
+	#[test]
+	fn workflow_invalid_transitions() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+			assert_ok!(GridPurchaseOrder::accept_version(
+				Origin::signed(SELLER), TEST_PO_ID.to_vec(), SELLER_ORG.to_vec(), INITIAL_PO_VERSION));
+
+			// Only the seller confirms
+			assert_noop!(set_state(BUYER, BUYER_ORG, WorkflowState::Confirmed), ERR_INVALID_WORKFLOW_TRANSITION);
+			// An issued order cannot be closed
+			assert_noop!(set_state(BUYER, BUYER_ORG, WorkflowState::Closed), ERR_INVALID_WORKFLOW_TRANSITION);
+			assert_noop!(set_state(SELLER, SELLER_ORG, WorkflowState::Issued), ERR_INVALID_WORKFLOW_TRANSITION);
+		})
+	}
+
+	#[test]
+	fn workflow_cancel() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			assert_ok!(set_state(SELLER, SELLER_ORG, WorkflowState::Cancelled));
+
+			assert_noop!(set_state(BUYER, BUYER_ORG, WorkflowState::Cancelled), ERR_PO_IS_COMPLETED);
+		})
+	}
+
+	#[test]
+	fn workflow_from_agent_of_other_party() {
+		with_externalities(&mut build_ext(), || {
+			create_test_po();
+
+			// The buyer's agent cannot act for the seller org
+			assert_noop!(
+				set_state(BUYER, SELLER_ORG, WorkflowState::Cancelled),
+				crate::grid_pike::ERR_SENDER_MUST_BE_ORG_AGENT
+			);
+		})
+	}
+}
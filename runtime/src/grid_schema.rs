@@ -4,22 +4,58 @@
 use crate::grid_pike::{OrgId, validate_org_id};
 use crate::grid_pike::Trait as PikeTrait;
 use crate::grid_pike::Module as PikeModule;
+use crate::grid_offchain::{OffchainRef, validate_offchain_ref};
 use rstd::prelude::*;
-use parity_codec::{Decode, Encode};
-// use runtime_io::{with_storage, StorageOverlay, ChildrenStorageOverlay};
-// use runtime_primitives::traits::Hash;
+use parity_codec::{Compact, Decode, Encode, Input};
+#[cfg(feature = "std")]
+use runtime_io::with_storage;
+#[cfg(feature = "std")]
+use serde_derive::{Deserialize, Serialize};
 use support::{
 	decl_module, decl_storage, decl_event,
 	ensure, StorageMap,
 	dispatch::Result
 };
-use system::ensure_signed;
+use system::{ensure_root, ensure_signed};
 
 const ERR_SCHEMA_NAME_REQUIRED: &str = "Schema name required";
 const ERR_SCHEMA_NAME_TOO_LONG: &str = "Schema name too long";
 const ERR_SCHEMA_ALREADY_EXISTS: &str = "Schema already exists";
+pub const ERR_SCHEMA_DOES_NOT_EXIST: &str = "Schema does not exist";
+pub const ERR_SCHEMA_RETIRED: &str = "Schema is retired";
+const ERR_SCHEMA_UNDECODABLE: &str = "Schema of the legacy layout cannot be decoded";
+const ERR_SCHEMA_TRANSFER_TO_OWNER: &str = "Schema is already owned by the receiving organization";
+const ERR_SCHEMA_TRANSFER_DOES_NOT_EXIST: &str = "Schema has no pending transfer";
+const ERR_SCHEMA_PROPERTY_REMOVED: &str = "Existing schema property cannot be removed";
+const ERR_SCHEMA_PROPERTY_REDEFINED: &str = "Existing schema property cannot be redefined";
+const ERR_SCHEMA_NEW_PROPERTY_REQUIRED: &str = "New schema property cannot be required";
+const ERR_SCHEMA_PROPERTY_NAME_REQUIRED: &str = "Schema property name required";
+const ERR_SCHEMA_PROPERTY_NAME_TOO_LONG: &str = "Schema property name too long";
+const ERR_SCHEMA_PROPERTY_NAME_DUPLICATED: &str = "Schema property name must be unique";
+const ERR_SCHEMA_ENUM_OPTIONS_REQUIRED: &str = "Schema enum property requires at least one option";
+const ERR_SCHEMA_STRUCT_PROPERTIES_REQUIRED: &str = "Schema struct property requires nested properties";
+const ERR_SCHEMA_PROPERTIES_TOO_DEEP: &str = "Schema struct properties nested too deeply";
+pub const ERR_PROPERTY_VALUE_REQUIRED: &str = "Required property value missing";
+pub const ERR_PROPERTY_VALUE_UNDEFINED: &str = "Property value not defined in schema";
+pub const ERR_PROPERTY_VALUE_DUPLICATED: &str = "Property value must be unique";
+pub const ERR_PROPERTY_VALUE_TYPE_MISMATCH: &str = "Property value does not match schema data type";
+pub const ERR_PROPERTY_VALUE_EXPONENT_MISMATCH: &str = "Property number value does not match schema exponent";
+pub const ERR_PROPERTY_VALUE_ENUM_OUT_OF_RANGE: &str = "Property enum value out of range";
+pub const ERR_PROPERTY_VALUE_LATLONG_OUT_OF_BOUNDS: &str = "Property lat/long value out of bounds";
 
 const BYTEARRAY_LIMIT: usize = 100;
+const STRUCT_DEPTH_LIMIT: usize = 3;
+// Nested data is decoded one level deeper than allowed, so that validation reports it,
+// but no further, so that a deeply nested extrinsic can't overflow the stack while being decoded
+const DECODE_DEPTH_LIMIT: usize = STRUCT_DEPTH_LIMIT + 1;
+// Lat/long values are expressed in micro-degrees
+const LATITUDE_LIMIT: i64 = 90_000_000;
+const LONGITUDE_LIMIT: i64 = 180_000_000;
+
+pub const PERM_CAN_CREATE_SCHEMA: &[u8] = b"schema::can-create-schema";
+pub const PERM_CAN_UPDATE_SCHEMA: &[u8] = b"schema::can-update-schema";
+
+pub const INITIAL_SCHEMA_VERSION: u32 = 1;
 
 pub type Name = Vec<u8>;
 
@@ -29,23 +65,181 @@ fn validate_schema_name(name: &[u8]) -> Result {
     Ok(())
 }
 
-#[cfg_attr(feature = "std", derive(Debug))]
+pub fn validate_properties(properties: &[PropertyDefinition]) -> Result {
+	validate_nested_properties(properties, 0)
+}
+
+fn validate_nested_properties(properties: &[PropertyDefinition], depth: usize) -> Result {
+	ensure!(depth <= STRUCT_DEPTH_LIMIT, ERR_SCHEMA_PROPERTIES_TOO_DEEP);
+	for (i, property) in properties.iter().enumerate() {
+		ensure!(property.name.len() > 0, ERR_SCHEMA_PROPERTY_NAME_REQUIRED);
+		ensure!(property.name.len() <= BYTEARRAY_LIMIT, ERR_SCHEMA_PROPERTY_NAME_TOO_LONG);
+		ensure!(
+			!properties[..i].iter().any(|p| p.name == property.name),
+			ERR_SCHEMA_PROPERTY_NAME_DUPLICATED
+		);
+		match property.data_type {
+			DataType::Enum => ensure!(!property.enum_options.is_empty(), ERR_SCHEMA_ENUM_OPTIONS_REQUIRED),
+			DataType::Struct => {
+				ensure!(!property.struct_properties.is_empty(), ERR_SCHEMA_STRUCT_PROPERTIES_REQUIRED);
+				validate_nested_properties(&property.struct_properties, depth + 1)?;
+			},
+			_ => {}
+		}
+	}
+	Ok(())
+}
+
+/// Validates values against the property definitions of a schema version.
+pub fn validate_property_values(definitions: &[PropertyDefinition], values: &[PropertyValue]) -> Result {
+	validate_nested_values(definitions, values, false)
+}
+
+/// Validates a subset of values, e.g. an update, without enforcing required properties.
+pub fn validate_partial_property_values(definitions: &[PropertyDefinition], values: &[PropertyValue]) -> Result {
+	validate_nested_values(definitions, values, true)
+}
+
+fn validate_nested_values(definitions: &[PropertyDefinition], values: &[PropertyValue], partial: bool) -> Result {
+	for (i, property) in values.iter().enumerate() {
+		ensure!(
+			!values[..i].iter().any(|v| v.name == property.name),
+			ERR_PROPERTY_VALUE_DUPLICATED
+		);
+		let definition = definitions.iter()
+			.find(|d| d.name == property.name)
+			.ok_or(ERR_PROPERTY_VALUE_UNDEFINED)?;
+		validate_value(definition, &property.value)?;
+	}
+	if partial {
+		return Ok(());
+	}
+	for definition in definitions.iter().filter(|d| d.required) {
+		ensure!(
+			values.iter().any(|v| v.name == definition.name),
+			ERR_PROPERTY_VALUE_REQUIRED
+		);
+	}
+	Ok(())
+}
+
+fn validate_value(definition: &PropertyDefinition, value: &Value) -> Result {
+	match (&definition.data_type, value) {
+		(DataType::Bytes, Value::Bytes(_)) |
+		(DataType::Boolean, Value::Boolean(_)) |
+		(DataType::String, Value::String(_)) => Ok(()),
+		(DataType::Number, Value::Number(_, exponent)) => {
+			ensure!(*exponent == definition.number_exponent, ERR_PROPERTY_VALUE_EXPONENT_MISMATCH);
+			Ok(())
+		},
+		(DataType::Enum, Value::Enum(index)) => {
+			ensure!((*index as usize) < definition.enum_options.len(), ERR_PROPERTY_VALUE_ENUM_OUT_OF_RANGE);
+			Ok(())
+		},
+		// Struct values are always reported as a whole
+		(DataType::Struct, Value::Struct(values)) =>
+			validate_nested_values(&definition.struct_properties, values, false),
+		(DataType::LatLong, Value::LatLong(latitude, longitude)) => {
+			ensure!(
+				(-LATITUDE_LIMIT..=LATITUDE_LIMIT).contains(latitude)
+					&& (-LONGITUDE_LIMIT..=LONGITUDE_LIMIT).contains(longitude),
+				ERR_PROPERTY_VALUE_LATLONG_OUT_OF_BOUNDS
+			);
+			Ok(())
+		},
+		(DataType::OffchainRef, Value::OffchainRef(offchain_ref)) => validate_offchain_ref(offchain_ref),
+		_ => Err(ERR_PROPERTY_VALUE_TYPE_MISMATCH)
+	}
+}
+
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
 pub struct Schema {
 	pub name: Name,
 	pub owner: OrgId,
-	pub properties: Vec<PropertyDefinition>
+	// Current version, its properties are stored in SchemaVersions
+	pub version: u32,
+	pub status: SchemaStatus,
+}
+
+/// Lifecycle of a schema, set by its owner.
+/// New data can't be created against a retired schema, while using a deprecated one emits a warning.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum SchemaStatus {
+	Active,
+	Deprecated,
+	Retired,
+}
+
+impl Default for SchemaStatus {
+	fn default() -> Self {
+		SchemaStatus::Active
+	}
+}
+
+/// Schema of the original layout, keyed by name, holding the properties of its only version.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct LegacySchema {
+	pub name: Name,
+	pub owner: OrgId,
+	pub properties: Vec<LegacyPropertyDefinition>,
 }
 
+/// Property definition of the original layout, before descriptions, exponents, options & nesting.
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct LegacyPropertyDefinition {
+	pub name: Name,
+	pub data_type: DataType,
+	pub required: bool,
+}
+
+impl From<LegacySchema> for Schema {
+	fn from(legacy: LegacySchema) -> Self {
+		Schema {
+			name: legacy.name,
+			owner: legacy.owner,
+			version: INITIAL_SCHEMA_VERSION,
+			status: SchemaStatus::Active,
+		}
+	}
+}
+
+impl From<LegacyPropertyDefinition> for PropertyDefinition {
+	fn from(legacy: LegacyPropertyDefinition) -> Self {
+		PropertyDefinition {
+			name: legacy.name,
+			data_type: legacy.data_type,
+			required: legacy.required,
+			..Default::default()
+		}
+	}
+}
+
+impl LegacySchema {
+	fn definitions(&self) -> Vec<PropertyDefinition> {
+		self.properties.iter().cloned().map(PropertyDefinition::from).collect()
+	}
+}
+
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Clone, PartialEq, Eq)]
 pub struct PropertyDefinition {
 	pub name: Name,
 	pub data_type: DataType,
 	pub required: bool,
+	pub description: Vec<u8>,
+	// Fixed-point position of Number values, e.g. -2 for cents
+	pub number_exponent: i32,
+	// Allowed values of an Enum property
+	pub enum_options: Vec<Vec<u8>>,
+	// Nested properties of a Struct property
+	pub struct_properties: Vec<PropertyDefinition>,
 }
 
-#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
 #[derive(Encode, Decode, Clone, PartialEq, Eq)]
 pub enum DataType {
     Bytes,
@@ -55,6 +249,105 @@ pub enum DataType {
     Enum,
     Struct,
     LatLong,
+    OffchainRef,
+}
+
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Clone, PartialEq, Eq)]
+pub struct PropertyValue {
+	pub name: Name,
+	pub value: Value,
+}
+
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Clone, PartialEq, Eq)]
+pub enum Value {
+	Bytes(Vec<u8>),
+	Boolean(bool),
+	// Fixed-point number, i.e. value * 10^exponent
+	Number(i64, i32),
+	String(Vec<u8>),
+	// Index into the definition enum_options
+	Enum(u32),
+	Struct(Vec<PropertyValue>),
+	// Latitude & longitude, in micro-degrees
+	LatLong(i64, i64),
+	// Bulky data, e.g. an image, anchored by its hash
+	OffchainRef(OffchainRef),
+}
+
+// Recursive types are decoded by hand, to bound their nesting
+impl Decode for PropertyDefinition {
+	fn decode<I: Input>(input: &mut I) -> Option<Self> {
+		Self::decode_nested(input, 0)
+	}
+}
+
+impl PropertyDefinition {
+	fn decode_nested<I: Input>(input: &mut I, depth: usize) -> Option<Self> {
+		Some(PropertyDefinition {
+			name: Decode::decode(input)?,
+			data_type: Decode::decode(input)?,
+			required: Decode::decode(input)?,
+			description: Decode::decode(input)?,
+			number_exponent: Decode::decode(input)?,
+			enum_options: Decode::decode(input)?,
+			struct_properties: decode_nested_vec(input, depth, Self::decode_nested)?,
+		})
+	}
+}
+
+impl Decode for PropertyValue {
+	fn decode<I: Input>(input: &mut I) -> Option<Self> {
+		Self::decode_nested(input, 0)
+	}
+}
+
+impl PropertyValue {
+	fn decode_nested<I: Input>(input: &mut I, depth: usize) -> Option<Self> {
+		Some(PropertyValue {
+			name: Decode::decode(input)?,
+			value: Value::decode_nested(input, depth)?,
+		})
+	}
+}
+
+impl Decode for Value {
+	fn decode<I: Input>(input: &mut I) -> Option<Self> {
+		Self::decode_nested(input, 0)
+	}
+}
+
+impl Value {
+	// Variants are indexed in order of declaration, as by the derived encoding
+	fn decode_nested<I: Input>(input: &mut I, depth: usize) -> Option<Self> {
+		match input.read_byte()? {
+			0 => Some(Value::Bytes(Decode::decode(input)?)),
+			1 => Some(Value::Boolean(Decode::decode(input)?)),
+			2 => Some(Value::Number(Decode::decode(input)?, Decode::decode(input)?)),
+			3 => Some(Value::String(Decode::decode(input)?)),
+			4 => Some(Value::Enum(Decode::decode(input)?)),
+			5 => Some(Value::Struct(decode_nested_vec(input, depth, PropertyValue::decode_nested)?)),
+			6 => Some(Value::LatLong(Decode::decode(input)?, Decode::decode(input)?)),
+			7 => Some(Value::OffchainRef(Decode::decode(input)?)),
+			_ => None
+		}
+	}
+}
+
+// Decodes the items nested in an item at the given depth, encoded as a Vec
+fn decode_nested_vec<I: Input, T>(
+	input: &mut I, depth: usize,
+	decode_item: fn(&mut I, usize) -> Option<T>) -> Option<Vec<T>> {
+	let len = <Compact<u32>>::decode(input)?.0;
+	if len > 0 && depth >= DECODE_DEPTH_LIMIT {
+		return None;
+	}
+	let mut items = Vec::new();
+	for _ in 0..len {
+		items.push(decode_item(input, depth + 1)?);
+	}
+	Some(items)
 }
 
 impl Default for PropertyDefinition {
@@ -63,8 +356,11 @@ impl Default for PropertyDefinition {
 			name: vec!(),
 			data_type: DataType::Bytes,
 			required: false,
-			// number_exponent: 0
-		} 
+			description: vec!(),
+			number_exponent: 0,
+			enum_options: vec!(),
+			struct_properties: vec!(),
+		}
 	}
 }
 
@@ -72,7 +368,8 @@ impl Default for PropertyDefinition {
 pub struct SchemaBuilder {
 	name: Name,
 	owner: OrgId,
-	properties: Vec<PropertyDefinition>
+	version: u32,
+	status: SchemaStatus,
 }
 impl SchemaBuilder {
 	pub fn with_name(mut self, name: Name) -> Self {
@@ -85,10 +382,15 @@ impl SchemaBuilder {
 		self
 	}
 
-	pub fn with_properties(mut self, properties: Vec<PropertyDefinition>) -> Self {
-		self.properties = properties;
+	pub fn with_version(mut self, version: u32) -> Self {
+		self.version = version;
+		self
+	}
+
+	pub fn with_status(mut self, status: SchemaStatus) -> Self {
+		self.status = status;
 		self
-	} 
+	}
 
 	pub fn build(self) -> rstd::result::Result<Schema, &'static str> {
 		validate_schema_name(&self.name)?;
@@ -96,7 +398,8 @@ impl SchemaBuilder {
 		let mut schema = Schema::default();
 		schema.name = self.name;
 		schema.owner = self.owner;
-		schema.properties = self.properties;
+		schema.version = self.version;
+		schema.status = self.status;
 		Ok(schema)
 	}
 }
@@ -107,7 +410,44 @@ pub trait Trait: PikeTrait {
 
 decl_storage! {
 	trait Store for Module<T: Trait> as GridSchema {
-		Schemas get(schema_by_name): map Name => Option<Schema>;
+		// Schemas of the original layout, keyed by name, moved to the indexed ones by `migrate_schemas`
+		Schemas get(legacy_schema): map Name => Option<LegacySchema>;
+		IndexedSchemas get(schema_by_index): map u32 => Option<Schema>;
+		SchemasCount get(schemas_count): u32;
+		SchemasIndex get(schema_index): map Name => u32;
+		// Immutable property definitions of every version of a schema, by schema index
+		IndexedSchemaVersions get(schema_version_by_index): map (u32, u32) => Option<Vec<PropertyDefinition>>;
+		// Indexes of the schemas owned by an organization, by organization index
+		SchemasOfOrg get(schema_indexes_of_org): map u32 => Vec<u32>;
+		// Index of the organization a schema has been offered to, until it accepts it, by schema index
+		SchemaTransfers get(schema_transfer): map u32 => Option<u32>;
+	}
+
+	add_extra_genesis {
+		// Pre-registered schemas, owned by genesis organizations
+		config(schemas): Vec<(Name, OrgId, Vec<PropertyDefinition>)>;
+
+		build(|storage: &mut runtime_primitives::StorageOverlay,
+			   _: &mut runtime_primitives::ChildrenStorageOverlay,
+			   config: &GenesisConfig<T>| {
+			with_storage(storage, || {
+				for (name, owner, properties) in &config.schemas {
+					let schema = SchemaBuilder::default()
+						.with_name(name.clone())
+						.with_owner(owner.clone())
+						.with_version(INITIAL_SCHEMA_VERSION)
+						.build()
+						.expect("Invalid genesis schema");
+					validate_properties(properties).expect("Invalid genesis schema properties");
+					<Module<T>>::validate_new_schema(name).expect("Duplicate genesis schema");
+					<PikeModule<T>>::validate_existing_org(owner).expect("Unknown genesis schema owner");
+					let schema_idx = <Module<T>>::insert_schema_version(schema, properties.clone())
+						.expect("Too many genesis schemas");
+					<Module<T>>::insert_org_schema(owner, schema_idx)
+						.expect("Too many genesis organizations");
+				}
+			});
+		});
 	}
 }
 
@@ -116,6 +456,14 @@ decl_event!(
 	// where <T as system::Trait>::AccountId
 	{
 		SchemaCreated(Name, OrgId),
+		SchemaUpdated(Name, OrgId),
+		// Schema, current owner, receiving org
+		SchemaTransferOffered(Name, OrgId, OrgId),
+		SchemaTransferred(Name, OrgId, OrgId),
+		SchemaStatusChanged(Name, OrgId, SchemaStatus),
+		// Warns that new data was created against a deprecated schema
+		DeprecatedSchemaUsed(Name),
+		SchemasMigrated(u32),
 	}
 );
 
@@ -131,24 +479,147 @@ decl_module! {
 			let schema = SchemaBuilder::default()
 				.with_name(name.clone())
 				.with_owner(owner.clone())
-				.with_properties(properties)
+				.with_version(INITIAL_SCHEMA_VERSION)
 				.build()?;
+			validate_properties(&properties)?;
 			Self::validate_new_schema(&name)?;
 
 			// Validate org exists
 			<PikeModule<T>>::validate_existing_org(&owner)?;
-			// Validate signer is an active agent of the specified org
-			<PikeModule<T>>::validate_is_org_active_agent(&sender, owner.clone())?;
+			// Validate signer is an active agent of the specified org,
+			// with the right to create schema
+			<PikeModule<T>>::ensure_permission(&sender, owner.clone(), PERM_CAN_CREATE_SCHEMA)?;
+
+			let schema_idx = Self::insert_schema_version(schema, properties)?;
+			Self::insert_org_schema(&owner, schema_idx)?;
+
+			Self::deposit_event(Event::SchemaCreated(name, owner));
+
+			Ok(())
+		}
+
+		/// Creates a new version of a schema, given its complete new list of properties.
+		/// Properties can only be appended, so that existing data remains valid.
+		pub fn update_schema(
+			origin, name: Name,
+			properties: Vec<PropertyDefinition>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			validate_schema_name(&name)?;
+			validate_properties(&properties)?;
+			let (existing, current_properties) = Self::validate_existing_schema(&name)?;
+			ensure!(existing.status != SchemaStatus::Retired, ERR_SCHEMA_RETIRED);
+			let owner = existing.owner.clone();
+			let version = existing.version.checked_add(1)
+				.ok_or("Overflow adding a new schema version")?;
+			let schema = SchemaBuilder::default()
+				.with_name(name.clone())
+				.with_owner(owner.clone())
+				.with_version(version)
+				.with_status(existing.status)
+				.build()?;
+			let properties = Self::append_properties(&current_properties, properties)?;
 
-			// Check whether signer has the right to create schema
-			// TODO: add has_permission fn to grid_pike module (permission = role)
-			// check_permission(perm_checker, signer, "can_create_schema")?;
+			// Validate signer is an active agent of the owner org,
+			// with the right to update schema
+			<PikeModule<T>>::ensure_permission(&sender, owner.clone(), PERM_CAN_UPDATE_SCHEMA)?;
 
-			//TODO: add properties validation (name, data_type & related props)
+			Self::insert_schema_version(schema, properties)?;
 
-			<Schemas<T>>::insert(&name, schema);
+			Self::deposit_event(Event::SchemaUpdated(name, owner));
 
-			Self::deposit_event(Event::SchemaCreated(name, owner));
+			Ok(())
+		}
+
+		/// Deprecates or retires a schema, or makes it active again.
+		pub fn set_schema_status(origin, name: Name, status: SchemaStatus) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			validate_schema_name(&name)?;
+			let (mut schema, _) = Self::validate_existing_schema(&name)?;
+			let owner = schema.owner.clone();
+
+			// Validate signer is an active agent of the owner org,
+			// with the right to update schema
+			<PikeModule<T>>::ensure_permission(&sender, owner.clone(), PERM_CAN_UPDATE_SCHEMA)?;
+
+			schema.status = status.clone();
+			Self::insert_schema(schema)?;
+
+			Self::deposit_event(Event::SchemaStatusChanged(name, owner, status));
+
+			Ok(())
+		}
+
+		/// Offers a schema to another organization, which becomes its owner once it accepts.
+		/// A new offer replaces the pending one.
+		pub fn offer_schema(origin, name: Name, receiving_org: OrgId) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			validate_schema_name(&name)?;
+			validate_org_id(&receiving_org)?;
+			let (schema, _) = Self::validate_existing_schema(&name)?;
+			ensure!(schema.owner != receiving_org, ERR_SCHEMA_TRANSFER_TO_OWNER);
+			<PikeModule<T>>::validate_existing_org(&receiving_org)?;
+
+			// Validate signer is an active admin of the owner org
+			<PikeModule<T>>::validate_is_org_active_agent(&sender, schema.owner.clone())?;
+			<PikeModule<T>>::validate_is_agent_admin(&sender, &schema.owner)?;
+
+			let schema_idx = Self::index_schema(&name)?;
+			let receiving_org_idx = <PikeModule<T>>::index_org(&receiving_org)?;
+			<SchemaTransfers<T>>::insert(schema_idx, receiving_org_idx);
+
+			Self::deposit_event(Event::SchemaTransferOffered(name, schema.owner, receiving_org));
+
+			Ok(())
+		}
+
+		/// Accepts a schema offered to the sender's organization.
+		/// The previous owner can no longer update the schema afterwards.
+		pub fn accept_schema(origin, name: Name) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			validate_schema_name(&name)?;
+			let (mut schema, _) = Self::validate_existing_schema(&name)?;
+			let (schema_idx, receiving_org_idx) = Self::find_schema_index(&name)
+				.and_then(|schema_idx| Self::schema_transfer(schema_idx).map(|org_idx| (schema_idx, org_idx)))
+				.ok_or(ERR_SCHEMA_TRANSFER_DOES_NOT_EXIST)?;
+			let receiving_org = <PikeModule<T>>::org_by_index(receiving_org_idx)
+				.ok_or(ERR_SCHEMA_TRANSFER_DOES_NOT_EXIST)?.id;
+
+			// Validate signer is an active admin of the receiving org
+			<PikeModule<T>>::validate_is_org_active_agent(&sender, receiving_org.clone())?;
+			<PikeModule<T>>::validate_is_agent_admin(&sender, &receiving_org)?;
+
+			let previous_owner = schema.owner.clone();
+			schema.owner = receiving_org.clone();
+			Self::insert_schema(schema)?;
+			<SchemaTransfers<T>>::remove(schema_idx);
+			if let Some(previous_owner_idx) = <PikeModule<T>>::find_org_index(&previous_owner) {
+				<SchemasOfOrg<T>>::mutate(previous_owner_idx, |idxs| idxs.retain(|idx| *idx != schema_idx));
+			}
+			Self::insert_org_schema(&receiving_org, schema_idx)?;
+
+			Self::deposit_event(Event::SchemaTransferred(name, previous_owner, receiving_org));
+
+			Ok(())
+		}
+
+		/// Moves schemas of the original layout, keyed by name, to the u32 indexed one, their properties becoming version 1.
+		/// Names without such a legacy schema are skipped, so batches can safely be replayed.
+		pub fn migrate_schemas(origin, names: Vec<Name>) -> Result {
+			ensure_root(origin)?;
+
+			let mut migrated: u32 = 0;
+			for name in names {
+				if Self::legacy_schema(&name).is_some() {
+					Self::index_schema(&name)?;
+					migrated += 1;
+				}
+			}
+
+			Self::deposit_event(Event::SchemasMigrated(migrated));
 
 			Ok(())
 		}
@@ -156,21 +627,184 @@ decl_module! {
 }
 
 impl<T: Trait> Module<T> {
+	// PUBLIC IMMUTABLES
+
+	/// Returns the index of a schema, unless it's still in the original layout.
+	pub fn find_schema_index(name: &[u8]) -> Option<u32> {
+		if <SchemasIndex<T>>::exists(name.to_vec()) {
+			Some(Self::schema_index(name.to_vec()))
+		} else {
+			None
+		}
+	}
+
+	/// Returns a schema by name.
+	/// Schemas not migrated yet are looked up in the original layout.
+	pub fn schema_by_name(name: &[u8]) -> Option<Schema> {
+		if let Some(schema_idx) = Self::find_schema_index(name) {
+			Self::schema_by_index(schema_idx)
+		} else {
+			Self::legacy_schema(name.to_vec()).map(Schema::from)
+		}
+	}
+
+	/// Returns the property definitions of a version of a schema.
+	pub fn schema_version(name: &[u8], version: u32) -> Option<Vec<PropertyDefinition>> {
+		if let Some(schema_idx) = Self::find_schema_index(name) {
+			Self::schema_version_by_index((schema_idx, version))
+		} else {
+			Self::legacy_schema(name.to_vec())
+				.filter(|_| version == INITIAL_SCHEMA_VERSION)
+				.map(|legacy| legacy.definitions())
+		}
+	}
+
+	/// Returns a schema along with the property definitions of its current version.
+	pub fn current_schema(name: &[u8]) -> Option<(Schema, Vec<PropertyDefinition>)> {
+		let schema = Self::schema_by_name(name)?;
+		let properties = Self::schema_version(name, schema.version)?;
+		Some((schema, properties))
+	}
+
+	/// Returns the schemas owned by an organization.
+	pub fn schemas_of_org(org_id: &[u8]) -> Vec<Schema> {
+		<PikeModule<T>>::find_org_index(org_id)
+			.map_or(vec![], Self::schema_indexes_of_org)
+			.into_iter()
+			.filter_map(Self::schema_by_index)
+			.collect()
+	}
+
+	/// Validates property values against the current version of a schema.
+	pub fn validate_values(schema_name: &[u8], values: &[PropertyValue]) -> Result {
+		let (_, definitions) = Self::validate_existing_schema(schema_name)?;
+		validate_property_values(&definitions, values)
+	}
+
+	/// Returns the current version of a schema that new data can be created against, i.e. not retired.
+	pub fn usable_schema(name: &[u8])
+		-> rstd::result::Result<(Schema, Vec<PropertyDefinition>), &'static str> {
+		let (schema, definitions) = Self::validate_existing_schema(name)?;
+		ensure!(schema.status != SchemaStatus::Retired, ERR_SCHEMA_RETIRED);
+		Ok((schema, definitions))
+	}
+
+	/// Same as `validate_values`, for new data.
+	pub fn validate_new_values(schema_name: &[u8], values: &[PropertyValue]) -> Result {
+		let (_, definitions) = Self::usable_schema(schema_name)?;
+		validate_property_values(&definitions, values)
+	}
+
+	/// To be called once new data has been stored against a schema.
+	pub fn warn_if_deprecated(schema_name: &[u8]) {
+		if let Some(schema) = Self::schema_by_name(schema_name) {
+			if schema.status == SchemaStatus::Deprecated {
+				Self::deposit_event(Event::DeprecatedSchemaUsed(schema.name));
+			}
+		}
+	}
 
 	// Helpers
     fn validate_new_schema(name: &[u8]) -> Result {
-        ensure!(!<Schemas<T>>::exists::<Vec<u8>>(name.into()), ERR_SCHEMA_ALREADY_EXISTS);
+        ensure!(Self::schema_by_name(name).is_none(), ERR_SCHEMA_ALREADY_EXISTS);
         Ok(())
     }
+
+	fn validate_existing_schema(name: &[u8])
+		-> rstd::result::Result<(Schema, Vec<PropertyDefinition>), &'static str> {
+		Self::current_schema(name).ok_or(ERR_SCHEMA_DOES_NOT_EXIST)
+	}
+
+	/// Checks that every existing property is kept as-is, and appends the new ones.
+	fn append_properties(
+		existing: &[PropertyDefinition],
+		properties: Vec<PropertyDefinition>) -> rstd::result::Result<Vec<PropertyDefinition>, &'static str> {
+		for current in existing {
+			match properties.iter().find(|p| p.name == current.name) {
+				Some(property) => ensure!(property == current, ERR_SCHEMA_PROPERTY_REDEFINED),
+				None => return Err(ERR_SCHEMA_PROPERTY_REMOVED)
+			}
+		}
+
+		let mut appended = existing.to_vec();
+		for property in properties {
+			if !existing.iter().any(|p| p.name == property.name) {
+				// Data recorded against the previous definition would lack it
+				ensure!(!property.required, ERR_SCHEMA_NEW_PROPERTY_REQUIRED);
+				appended.push(property);
+			}
+		}
+		Ok(appended)
+	}
+
+	// PRIVATE MUTABLES
+	fn insert_org_schema(org_id: &[u8], schema_idx: u32) -> Result {
+		let org_idx = <PikeModule<T>>::index_org(org_id)?;
+		Self::add_org_schema(org_idx, schema_idx);
+		Ok(())
+	}
+
+	fn add_org_schema(org_idx: u32, schema_idx: u32) {
+		<SchemasOfOrg<T>>::mutate(org_idx, |idxs| if !idxs.contains(&schema_idx) {
+			idxs.push(schema_idx)
+		});
+	}
+
+	fn insert_schema_version(
+		schema: Schema,
+		properties: Vec<PropertyDefinition>) -> rstd::result::Result<u32, &'static str> {
+		let schema_idx = Self::index_schema(&schema.name)?;
+		<IndexedSchemaVersions<T>>::insert((schema_idx, schema.version), properties);
+		<IndexedSchemas<T>>::insert(schema_idx, schema);
+		Ok(schema_idx)
+	}
+
+	fn insert_schema(schema: Schema) -> Result {
+		let schema_idx = Self::index_schema(&schema.name)?;
+		<IndexedSchemas<T>>::insert(schema_idx, schema);
+		Ok(())
+	}
+
+	/// Returns the index of a schema, allocating one if it's new.
+	/// A schema still in the original layout is moved to it, its properties becoming version 1.
+	pub fn index_schema(name: &[u8]) -> rstd::result::Result<u32, &'static str> {
+		if let Some(schema_idx) = Self::find_schema_index(name) {
+			return Ok(schema_idx);
+		}
+
+		// A legacy schema that can't be decoded is left in place rather than shadowed
+		let legacy = Self::legacy_schema(name.to_vec());
+		ensure!(legacy.is_some() || !<Schemas<T>>::exists(name.to_vec()), ERR_SCHEMA_UNDECODABLE);
+		let legacy_owner_idx = match &legacy {
+			Some(legacy) => Some(<PikeModule<T>>::index_org(&legacy.owner)?),
+			None => None,
+		};
+
+		let new_schema_idx = Self::schemas_count().checked_add(1)
+			.ok_or("Overflow adding a new schema")?;
+		<SchemasIndex<T>>::insert(name.to_vec(), new_schema_idx);
+		<SchemasCount<T>>::put(new_schema_idx);
+
+		if let (Some(legacy), Some(owner_idx)) = (legacy, legacy_owner_idx) {
+			<IndexedSchemaVersions<T>>::insert((new_schema_idx, INITIAL_SCHEMA_VERSION), legacy.definitions());
+			// Schemas of the original layout weren't listed by organization
+			Self::add_org_schema(owner_idx, new_schema_idx);
+			<Schemas<T>>::remove(name.to_vec());
+			<IndexedSchemas<T>>::insert(new_schema_idx, Schema::from(legacy));
+		}
+		Ok(new_schema_idx)
+	}
 }
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use super::*;
 	use crate::grid_pike::{
-		ERR_ORG_DOES_NOT_EXIST, ERR_SENDER_IS_NOT_AN_AGENT,
-		ERR_SENDER_MUST_BE_ORG_AGENT, ERR_SENDER_MUST_BE_ACTIVE_ADMIN};
-	use crate::grid_pike::tests::{store_test_org, store_test_agent, store_admin_role};
+		ERR_ORG_DOES_NOT_EXIST, ERR_SENDER_IS_NOT_AN_AGENT, ERR_SENDER_MUST_BE_ORG_AGENT,
+		ERR_SENDER_MUST_BE_ACTIVE_ADMIN, ERR_SENDER_MUST_BE_ORG_ADMIN, ERR_SENDER_MISSING_PERMISSION};
+	use crate::grid_offchain::{HashAlgorithm, ERR_OFFCHAIN_URI_REQUIRED};
+	use crate::grid_pike::tests::{
+		store_test_org, store_test_agent, store_admin_role, store_test_role, test_org_index};
 
     use primitives::{Blake2Hasher, H256};
     use runtime_io::with_externalities;
@@ -218,6 +852,18 @@ mod tests {
         t.into()
     }
 
+    fn build_ext_with_genesis(
+		pike: crate::grid_pike::GenesisConfig<GridSchemaTest>,
+		schema: GenesisConfig<GridSchemaTest>) -> runtime_io::TestExternalities<Blake2Hasher> {
+        let (mut t, mut c) = system::GenesisConfig::<GridSchemaTest>::default()
+            .build_storage()
+            .unwrap();
+        // Schema owners must exist, so build on top of the Pike genesis storage
+        pike.assimilate_storage(&mut t, &mut c).unwrap();
+        schema.assimilate_storage(&mut t, &mut c).unwrap();
+        t.into()
+    }
+
     const TEST_ORG_ID: &str = "did:example:123456789abcdefghijk";
 	const TEST_SCHEMA_NAME: &str = "asset";
     const TEST_ORG_NAME: &str = "Parity Tech";
@@ -228,18 +874,78 @@ mod tests {
 	const WEIGHT_PROP: &[u8] = b"weight";
 	const TEMP_PROP: &[u8] = b"temperature";
 	const LOCATION_PROP: &[u8] = b"location";
+	const IMAGE_PROP: &[u8] = b"image";
 
 	fn store_test_schema(name: Name, owner: OrgId) {
-		Schemas::<GridSchemaTest>::insert(
-			name.clone(),
+		store_test_schema_with_properties(name, owner, vec!());
+	}
+
+	pub fn store_test_schema_with_properties(name: Name, owner: OrgId, properties: Vec<PropertyDefinition>) {
+		let new_schema_idx = SchemasCount::<GridSchemaTest>::get() + 1;
+		SchemasIndex::<GridSchemaTest>::insert(&name, new_schema_idx);
+		SchemasCount::<GridSchemaTest>::put(new_schema_idx);
+		IndexedSchemaVersions::<GridSchemaTest>::insert((new_schema_idx, INITIAL_SCHEMA_VERSION), properties);
+		SchemasOfOrg::<GridSchemaTest>::mutate(test_org_index(&owner), |idxs| idxs.push(new_schema_idx));
+		IndexedSchemas::<GridSchemaTest>::insert(
+			new_schema_idx,
 			Schema {
 				name,
 				owner,
-				properties: vec!()
+				version: INITIAL_SCHEMA_VERSION,
+				status: SchemaStatus::Active
 			},
 		);
 	}
 
+	fn schema_names_of_org(org_id: &[u8]) -> Vec<Name> {
+		GridSchema::schemas_of_org(org_id).into_iter().map(|schema| schema.name).collect()
+	}
+
+	pub fn set_test_schema_status(name: &[u8], status: SchemaStatus) {
+		let schema_idx = SchemasIndex::<GridSchemaTest>::get(name.to_vec());
+		IndexedSchemas::<GridSchemaTest>::mutate(schema_idx, |schema| {
+			if let Some(schema) = schema.as_mut() {
+				schema.status = status;
+			}
+		});
+	}
+
+	// genesis config tests
+	#[test]
+	fn genesis_config_with_schemas() {
+		let admin = 1;
+		let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+		let owner = String::from(TEST_ORG_ID).into_bytes();
+		let properties = vec![
+			PropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::String, required: true, ..Default::default() },
+		];
+
+		let pike = crate::grid_pike::GenesisConfig::<GridSchemaTest> {
+			orgs: vec![(owner.clone(), String::from(TEST_ORG_NAME).into_bytes(), admin)],
+			roles: vec!(),
+		};
+		let config = GenesisConfig::<GridSchemaTest> {
+			schemas: vec![(schema.clone(), owner.clone(), properties.clone())],
+		};
+
+		with_externalities(&mut build_ext_with_genesis(pike, config), || {
+			assert_eq!(
+				GridSchema::schema_by_name(&schema),
+				Some(Schema {
+					name: schema.clone(),
+					owner: owner.clone(),
+					version: INITIAL_SCHEMA_VERSION,
+					status: SchemaStatus::Active
+				})
+			);
+			assert_eq!(
+				GridSchema::schema_version(&schema, INITIAL_SCHEMA_VERSION),
+				Some(properties)
+			);
+			assert_eq!(schema_names_of_org(&owner), vec![schema]);
+		})
+	}
+
     // create_schema tests
     #[test]
     fn create_schema_with_valid_args() {
@@ -250,14 +956,14 @@ mod tests {
 			let owner = String::from(TEST_ORG_ID).into_bytes();
 
 			let properties = vec![
-				PropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::String, required: true },
-				PropertyDefinition { name: WEIGHT_PROP.to_vec(), data_type: DataType::Number, required: false },
-				PropertyDefinition { name: TEMP_PROP.to_vec(), data_type: DataType::Number, required: false },
-				PropertyDefinition { name: LOCATION_PROP.to_vec(), data_type: DataType::LatLong, required: false },
+				PropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::String, required: true, ..Default::default() },
+				PropertyDefinition { name: WEIGHT_PROP.to_vec(), data_type: DataType::Number, required: false, ..Default::default() },
+				PropertyDefinition { name: TEMP_PROP.to_vec(), data_type: DataType::Number, required: false, ..Default::default() },
+				PropertyDefinition { name: LOCATION_PROP.to_vec(), data_type: DataType::LatLong, required: false, ..Default::default() },
 			];
 
 			// Store org & agent
-			let admin_role_id = store_admin_role();
+			let admin_role_id = store_admin_role(owner.clone());
 			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
 			store_test_agent(sender, owner.clone(), true, vec![admin_role_id]);
 
@@ -271,11 +977,17 @@ mod tests {
             assert_eq!(
                 GridSchema::schema_by_name(&schema),
                 Some(Schema {
-                    name: schema,
-                    owner: owner,
-					properties: properties
+                    name: schema.clone(),
+                    owner: owner.clone(),
+					version: INITIAL_SCHEMA_VERSION,
+					status: SchemaStatus::Active
                 })
             );
+			assert_eq!(
+				GridSchema::schema_version(&schema, INITIAL_SCHEMA_VERSION),
+				Some(properties)
+			);
+			assert_eq!(schema_names_of_org(&owner), vec![schema]);
         })
     }
 
@@ -351,7 +1063,7 @@ mod tests {
             let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
             let owner = String::from(TEST_ORG_NAME).into_bytes();
 
-			let _admin_role_id = store_admin_role();
+			let _admin_role_id = store_admin_role(owner.clone());
 			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
 
 			assert_noop!(
@@ -413,4 +1125,913 @@ mod tests {
             );
         })
     }
+
+	#[test]
+    fn create_schema_with_granted_permission() {
+        with_externalities(&mut build_ext(), || {
+			let agent = 1;
+            let owner = String::from(TEST_ORG_ID).into_bytes();
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+
+			let role_id = store_test_role(
+				owner.clone(), b"schema-editor".to_vec(), vec![PERM_CAN_CREATE_SCHEMA.to_vec()]);
+			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, owner.clone(), true, vec![role_id]);
+
+			assert_ok!(GridSchema::create_schema(Origin::signed(agent), schema.clone(), owner, vec!()));
+			assert!(GridSchema::schema_by_name(&schema).is_some());
+        })
+    }
+
+	#[test]
+    fn create_schema_with_permission_granted_by_other_org() {
+        with_externalities(&mut build_ext(), || {
+			let agent = 1;
+            let owner = String::from(TEST_ORG_ID).into_bytes();
+            let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+
+			// The agent is an editor of the other org only
+			let role_id = store_test_role(
+				other_org.clone(), b"schema-editor".to_vec(), vec![PERM_CAN_CREATE_SCHEMA.to_vec()]);
+			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_org(other_org.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, other_org.clone(), true, vec![role_id]);
+			store_test_agent(agent, owner.clone(), true, vec!());
+
+			assert_noop!(
+				GridSchema::create_schema(Origin::signed(agent), schema.clone(), owner, vec!()),
+				ERR_SENDER_MISSING_PERMISSION
+			);
+			assert_ok!(GridSchema::create_schema(Origin::signed(agent), schema, other_org, vec!()));
+        })
+    }
+
+	#[test]
+    fn create_schema_without_permission() {
+        with_externalities(&mut build_ext(), || {
+			let agent = 1;
+            let owner = String::from(TEST_ORG_ID).into_bytes();
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+
+			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(agent, owner.clone(), true, vec!());
+
+			assert_noop!(
+                GridSchema::create_schema(
+                    Origin::signed(agent),
+					schema,
+                    owner,
+                    vec!()
+                ),
+                ERR_SENDER_MISSING_PERMISSION
+            );
+        })
+    }
+
+	// update_schema tests
+	fn test_properties() -> Vec<PropertyDefinition> {
+		vec![
+			PropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::String, required: true, ..Default::default() },
+			PropertyDefinition { name: WEIGHT_PROP.to_vec(), data_type: DataType::Number, required: false, ..Default::default() },
+		]
+	}
+
+	fn setup_update_schema(sender: u64) -> (Name, OrgId) {
+		let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+		let owner = String::from(TEST_ORG_ID).into_bytes();
+
+		let admin_role_id = store_admin_role(owner.clone());
+		store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+		store_test_agent(sender, owner.clone(), true, vec![admin_role_id]);
+		store_test_schema_with_properties(schema.clone(), owner.clone(), test_properties());
+
+		(schema, owner)
+	}
+
+	#[test]
+	fn update_schema_with_appended_property() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let (schema, owner) = setup_update_schema(sender);
+
+			let temp = PropertyDefinition { name: TEMP_PROP.to_vec(), data_type: DataType::Number, required: false, ..Default::default() };
+			// Existing properties may be listed in any order
+			let mut properties = vec![temp.clone()];
+			properties.extend(test_properties().into_iter().rev());
+
+			assert_ok!(GridSchema::update_schema(Origin::signed(sender), schema.clone(), properties));
+
+			let mut expected = test_properties();
+			expected.push(temp);
+			assert_eq!(
+				GridSchema::schema_by_name(&schema),
+				Some(Schema {
+					name: schema.clone(),
+					owner: owner,
+					version: 2,
+					status: SchemaStatus::Active
+				})
+			);
+			assert_eq!(GridSchema::schema_version(&schema, 2), Some(expected));
+
+			// Previous version is left untouched
+			assert_eq!(
+				GridSchema::schema_version(&schema, INITIAL_SCHEMA_VERSION),
+				Some(test_properties())
+			);
+		})
+	}
+
+	#[test]
+	fn update_schema_with_unknown_schema() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridSchema::update_schema(
+					Origin::signed(1), String::from(TEST_SCHEMA_NAME).into_bytes(), vec!()),
+				ERR_SCHEMA_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn update_schema_removing_property() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let (schema, _) = setup_update_schema(sender);
+
+			let mut properties = test_properties();
+			properties.pop();
+
+			assert_noop!(
+				GridSchema::update_schema(Origin::signed(sender), schema, properties),
+				ERR_SCHEMA_PROPERTY_REMOVED
+			);
+		})
+	}
+
+	#[test]
+	fn update_schema_retyping_property() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let (schema, _) = setup_update_schema(sender);
+
+			let mut properties = test_properties();
+			properties[1].data_type = DataType::String;
+
+			assert_noop!(
+				GridSchema::update_schema(Origin::signed(sender), schema, properties),
+				ERR_SCHEMA_PROPERTY_REDEFINED
+			);
+		})
+	}
+
+	#[test]
+	fn update_schema_with_new_required_property() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let (schema, _) = setup_update_schema(sender);
+
+			let mut properties = test_properties();
+			properties.push(
+				PropertyDefinition { name: TEMP_PROP.to_vec(), data_type: DataType::Number, required: true, ..Default::default() });
+
+			assert_noop!(
+				GridSchema::update_schema(Origin::signed(sender), schema, properties),
+				ERR_SCHEMA_NEW_PROPERTY_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn update_schema_from_other_org() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let other = 2;
+			let (schema, _) = setup_update_schema(sender);
+			let other_org = String::from(TEST_EXISTING_ORG).into_bytes();
+
+			let admin_role_id = store_admin_role(other_org.clone());
+			store_test_org(other_org.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(other, other_org, true, vec![admin_role_id]);
+
+			assert_noop!(
+				GridSchema::update_schema(Origin::signed(other), schema, test_properties()),
+				ERR_SENDER_MUST_BE_ORG_AGENT
+			);
+		})
+	}
+
+	// offer_schema & accept_schema tests
+	fn setup_schema_transfer(sender: u64, receiver: u64) -> (Name, OrgId, OrgId) {
+		let (schema, owner) = setup_update_schema(sender);
+		let receiving_org = String::from(TEST_EXISTING_ORG).into_bytes();
+
+		let admin_role_id = store_admin_role(receiving_org.clone());
+		store_test_org(receiving_org.clone(), String::from(TEST_ORG_NAME).into_bytes());
+		store_test_agent(receiver, receiving_org.clone(), true, vec![admin_role_id]);
+
+		(schema, owner, receiving_org)
+	}
+
+	#[test]
+	fn transfer_schema_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let (sender, receiver) = (1, 2);
+			let (schema, owner, receiving_org) = setup_schema_transfer(sender, receiver);
+
+			assert_ok!(GridSchema::offer_schema(Origin::signed(sender), schema.clone(), receiving_org.clone()));
+			assert_eq!(
+				GridSchema::schema_transfer(GridSchema::schema_index(&schema)),
+				Some(test_org_index(&receiving_org))
+			);
+			// Ownership only changes once accepted
+			assert_eq!(GridSchema::schema_by_name(&schema).map(|s| s.owner), Some(owner.clone()));
+
+			assert_ok!(GridSchema::accept_schema(Origin::signed(receiver), schema.clone()));
+
+			assert_eq!(
+				GridSchema::schema_by_name(&schema),
+				Some(Schema {
+					name: schema.clone(),
+					owner: receiving_org.clone(),
+					version: INITIAL_SCHEMA_VERSION,
+					status: SchemaStatus::Active
+				})
+			);
+			assert_eq!(GridSchema::schema_transfer(GridSchema::schema_index(&schema)), None);
+			assert_eq!(schema_names_of_org(&owner), Vec::<Name>::new());
+			assert_eq!(schema_names_of_org(&receiving_org), vec![schema.clone()]);
+
+			// The previous owner has lost control of the schema, the new one has it
+			assert_noop!(
+				GridSchema::update_schema(Origin::signed(sender), schema.clone(), test_properties()),
+				ERR_SENDER_MUST_BE_ORG_AGENT
+			);
+			assert_ok!(GridSchema::update_schema(Origin::signed(receiver), schema, test_properties()));
+		})
+	}
+
+	#[test]
+	fn offer_schema_with_unknown_schema() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridSchema::offer_schema(
+					Origin::signed(1), String::from(TEST_SCHEMA_NAME).into_bytes(),
+					String::from(TEST_EXISTING_ORG).into_bytes()),
+				ERR_SCHEMA_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn offer_schema_to_owner() {
+		with_externalities(&mut build_ext(), || {
+			let (schema, owner, _) = setup_schema_transfer(1, 2);
+
+			assert_noop!(
+				GridSchema::offer_schema(Origin::signed(1), schema, owner),
+				ERR_SCHEMA_TRANSFER_TO_OWNER
+			);
+		})
+	}
+
+	#[test]
+	fn offer_schema_to_unknown_org() {
+		with_externalities(&mut build_ext(), || {
+			let (schema, _, _) = setup_schema_transfer(1, 2);
+
+			assert_noop!(
+				GridSchema::offer_schema(Origin::signed(1), schema, b"did:example:unknown".to_vec()),
+				ERR_ORG_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn offer_schema_without_admin_role() {
+		with_externalities(&mut build_ext(), || {
+			let editor = 3;
+			let (schema, owner, receiving_org) = setup_schema_transfer(1, 2);
+
+			// Editors may update the schema, but not give it away
+			let role_id = store_test_role(
+				owner.clone(), b"schema-editor".to_vec(), vec![PERM_CAN_UPDATE_SCHEMA.to_vec()]);
+			store_test_agent(editor, owner, true, vec![role_id]);
+
+			assert_noop!(
+				GridSchema::offer_schema(Origin::signed(editor), schema, receiving_org),
+				ERR_SENDER_MUST_BE_ORG_ADMIN
+			);
+		})
+	}
+
+	#[test]
+	fn accept_schema_without_offer() {
+		with_externalities(&mut build_ext(), || {
+			let (schema, _, _) = setup_schema_transfer(1, 2);
+
+			assert_noop!(
+				GridSchema::accept_schema(Origin::signed(2), schema),
+				ERR_SCHEMA_TRANSFER_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn accept_schema_from_other_org() {
+		with_externalities(&mut build_ext(), || {
+			let (schema, _, receiving_org) = setup_schema_transfer(1, 2);
+			assert_ok!(GridSchema::offer_schema(Origin::signed(1), schema.clone(), receiving_org));
+
+			// Only the receiving org may accept, not the current owner
+			assert_noop!(
+				GridSchema::accept_schema(Origin::signed(1), schema),
+				ERR_SENDER_MUST_BE_ORG_AGENT
+			);
+		})
+	}
+
+	// set_schema_status tests
+	#[test]
+	fn set_schema_status_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let (schema, _) = setup_update_schema(sender);
+
+			assert_ok!(GridSchema::set_schema_status(Origin::signed(sender), schema.clone(), SchemaStatus::Deprecated));
+			assert_eq!(GridSchema::schema_by_name(&schema).map(|s| s.status), Some(SchemaStatus::Deprecated));
+
+			// Deprecated schemas can still be updated, and remain deprecated
+			assert_ok!(GridSchema::update_schema(Origin::signed(sender), schema.clone(), test_properties()));
+			assert_eq!(
+				GridSchema::schema_by_name(&schema).map(|s| (s.version, s.status)),
+				Some((2, SchemaStatus::Deprecated))
+			);
+		})
+	}
+
+	#[test]
+	fn set_schema_status_without_permission() {
+		with_externalities(&mut build_ext(), || {
+			let agent = 2;
+			let (schema, owner) = setup_update_schema(1);
+			let role_id = store_test_role(owner.clone(), b"reader".to_vec(), vec!());
+			store_test_agent(agent, owner, true, vec![role_id]);
+
+			assert_noop!(
+				GridSchema::set_schema_status(Origin::signed(agent), schema, SchemaStatus::Retired),
+				ERR_SENDER_MISSING_PERMISSION
+			);
+		})
+	}
+
+	#[test]
+	fn update_schema_when_retired() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let (schema, _) = setup_update_schema(sender);
+			assert_ok!(GridSchema::set_schema_status(Origin::signed(sender), schema.clone(), SchemaStatus::Retired));
+
+			assert_noop!(
+				GridSchema::update_schema(Origin::signed(sender), schema, test_properties()),
+				ERR_SCHEMA_RETIRED
+			);
+		})
+	}
+
+	#[test]
+	fn validate_new_values_with_retired_schema() {
+		with_externalities(&mut build_ext(), || {
+			let (schema, _) = setup_update_schema(1);
+			let values = vec![PropertyValue { name: TYPE_PROP.to_vec(), value: Value::String(b"pallet".to_vec()) }];
+
+			set_test_schema_status(&schema, SchemaStatus::Deprecated);
+			assert_ok!(GridSchema::validate_new_values(&schema, &values));
+
+			// Existing data remains valid, but no new data can be created
+			set_test_schema_status(&schema, SchemaStatus::Retired);
+			assert_ok!(GridSchema::validate_values(&schema, &values));
+			assert_noop!(GridSchema::validate_new_values(&schema, &values), ERR_SCHEMA_RETIRED);
+		})
+	}
+
+	// migrate_schemas tests
+	/// Stores a schema as the original code did, keyed by name, without versions nor listing by organization.
+	fn store_legacy_schema(name: Name, owner: OrgId) {
+		Schemas::<GridSchemaTest>::insert(
+			name.clone(),
+			LegacySchema {
+				name,
+				owner,
+				properties: vec![
+					LegacyPropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::String, required: true },
+					LegacyPropertyDefinition { name: WEIGHT_PROP.to_vec(), data_type: DataType::Number, required: false },
+				],
+			},
+		);
+	}
+
+	#[test]
+	fn decode_original_schema_layout() {
+		let name = String::from(TEST_SCHEMA_NAME).into_bytes();
+		let owner = String::from(TEST_ORG_ID).into_bytes();
+		let original = (
+			name.clone(),
+			owner.clone(),
+			vec![(TYPE_PROP.to_vec(), DataType::String, true), (WEIGHT_PROP.to_vec(), DataType::Number, false)],
+		).encode();
+
+		let legacy = LegacySchema::decode(&mut &original[..]).unwrap();
+
+		assert_eq!(legacy.definitions(), test_properties());
+		assert_eq!(
+			Schema::from(legacy),
+			Schema { name, owner, version: INITIAL_SCHEMA_VERSION, status: SchemaStatus::Active }
+		);
+	}
+
+	#[test]
+	fn legacy_schema_found_until_migrated() {
+		with_externalities(&mut build_ext(), || {
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+			let owner = String::from(TEST_ORG_ID).into_bytes();
+
+			store_legacy_schema(schema.clone(), owner.clone());
+
+			assert_eq!(GridSchema::schema_by_name(&schema).map(|s| s.owner), Some(owner));
+			assert_eq!(GridSchema::schema_by_name(&schema).map(|s| s.status), Some(SchemaStatus::Active));
+			assert_eq!(GridSchema::schema_version(&schema, INITIAL_SCHEMA_VERSION), Some(test_properties()));
+			assert_eq!(GridSchema::schema_version(&schema, 2), None);
+		})
+	}
+
+	#[test]
+	fn migrate_schemas_moves_legacy_schemas() {
+		with_externalities(&mut build_ext(), || {
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+			let other_schema = b"other_schema".to_vec();
+			let owner = String::from(TEST_ORG_ID).into_bytes();
+
+			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_schema(other_schema.clone(), owner.clone());
+			store_legacy_schema(schema.clone(), owner.clone());
+			assert_eq!(GridSchema::schemas_of_org(&owner).len(), 1);
+
+			// Unknown and already indexed schemas are skipped
+			assert_ok!(GridSchema::migrate_schemas(
+				Origin::ROOT, vec![schema.clone(), other_schema.clone(), b"unknown".to_vec()]));
+
+			assert_eq!(GridSchema::legacy_schema(&schema), None);
+			assert_eq!(GridSchema::schemas_count(), 2);
+			assert_eq!(GridSchema::schema_index(&schema), 2);
+			assert_eq!(
+				GridSchema::schema_by_index(2),
+				Some(Schema {
+					name: schema.clone(),
+					owner: owner.clone(),
+					version: INITIAL_SCHEMA_VERSION,
+					status: SchemaStatus::Active
+				})
+			);
+			assert_eq!(GridSchema::schema_version_by_index((2, INITIAL_SCHEMA_VERSION)), Some(test_properties()));
+			assert_eq!(schema_names_of_org(&owner), vec![other_schema, schema.clone()]);
+
+			// Replaying a batch is harmless
+			assert_ok!(GridSchema::migrate_schemas(Origin::ROOT, vec![schema.clone()]));
+			assert_eq!(GridSchema::schemas_count(), 2);
+			assert_eq!(GridSchema::schema_index(&schema), 2);
+			assert_eq!(GridSchema::schemas_of_org(&owner).len(), 2);
+		})
+	}
+
+	#[test]
+	fn migrate_schemas_requires_root() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridSchema::migrate_schemas(Origin::signed(1), vec![TEST_SCHEMA_NAME.as_bytes().to_vec()]),
+				"bad origin: expected to be a root origin"
+			);
+		})
+	}
+
+	#[test]
+	fn update_schema_migrates_legacy_schema() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+			let owner = String::from(TEST_ORG_ID).into_bytes();
+			let mut properties = test_properties();
+			properties.push(PropertyDefinition { name: TEMP_PROP.to_vec(), data_type: DataType::Number, required: false, ..Default::default() });
+
+			let admin_role_id = store_admin_role(owner.clone());
+			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(sender, owner.clone(), true, vec![admin_role_id]);
+			store_legacy_schema(schema.clone(), owner.clone());
+
+			assert_ok!(GridSchema::update_schema(Origin::signed(sender), schema.clone(), properties.clone()));
+
+			assert_eq!(GridSchema::legacy_schema(&schema), None);
+			assert_eq!(GridSchema::schema_index(&schema), 1);
+			assert_eq!(GridSchema::schema_version(&schema, INITIAL_SCHEMA_VERSION), Some(test_properties()));
+			assert_eq!(GridSchema::schema_version(&schema, 2), Some(properties));
+			assert_eq!(schema_names_of_org(&owner), vec![schema]);
+		})
+	}
+
+	// schemas_of_org tests
+	#[test]
+	fn schemas_of_org_lists_owned_schemas() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let owner = String::from(TEST_ORG_ID).into_bytes();
+			let other = String::from(TEST_EXISTING_ORG).into_bytes();
+
+			let admin_role_id = store_admin_role(owner.clone());
+			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(sender, owner.clone(), true, vec![admin_role_id]);
+			let other_role_id = store_admin_role(other.clone());
+			store_test_org(other.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(2, other.clone(), true, vec![other_role_id]);
+
+			assert_ok!(GridSchema::create_schema(
+				Origin::signed(sender), b"asset".to_vec(), owner.clone(), test_properties()));
+			assert_ok!(GridSchema::create_schema(
+				Origin::signed(sender), b"pallet".to_vec(), owner.clone(), test_properties()));
+			assert_ok!(GridSchema::create_schema(
+				Origin::signed(2), b"truck".to_vec(), other.clone(), test_properties()));
+			// New versions don't list the schema twice
+			assert_ok!(GridSchema::update_schema(
+				Origin::signed(sender), b"asset".to_vec(), test_properties()));
+
+			let names: Vec<Name> = GridSchema::schemas_of_org(&owner)
+				.into_iter()
+				.map(|schema| schema.name)
+				.collect();
+			assert_eq!(names, vec![b"asset".to_vec(), b"pallet".to_vec()]);
+			assert_eq!(GridSchema::schemas_of_org(&owner)[0].version, 2);
+			assert_eq!(GridSchema::schemas_of_org(b"did:example:unknown"), vec!());
+		})
+	}
+
+	#[test]
+	fn property_definition_codec_roundtrip() {
+		let property = PropertyDefinition {
+			name: b"package".to_vec(),
+			data_type: DataType::Struct,
+			description: b"Packaging details".to_vec(),
+			struct_properties: vec![
+				PropertyDefinition {
+					name: b"material".to_vec(),
+					data_type: DataType::Enum,
+					enum_options: vec![b"paper".to_vec(), b"plastic".to_vec()],
+					..Default::default()
+				},
+				PropertyDefinition {
+					name: WEIGHT_PROP.to_vec(),
+					data_type: DataType::Number,
+					number_exponent: -3,
+					..Default::default()
+				},
+			],
+			..Default::default()
+		};
+
+		let encoded = property.encode();
+		assert_eq!(PropertyDefinition::decode(&mut &encoded[..]), Some(property));
+	}
+
+	// properties validation tests
+	fn assert_invalid_properties(properties: Vec<PropertyDefinition>, err: &'static str) {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridSchema::create_schema(
+					Origin::signed(1),
+					String::from(TEST_SCHEMA_NAME).into_bytes(),
+					String::from(TEST_ORG_ID).into_bytes(),
+					properties
+				),
+				err
+			);
+		})
+	}
+
+	fn struct_property(name: &[u8], struct_properties: Vec<PropertyDefinition>) -> PropertyDefinition {
+		PropertyDefinition {
+			name: name.to_vec(),
+			data_type: DataType::Struct,
+			struct_properties,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn create_schema_with_nested_properties() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+			let owner = String::from(TEST_ORG_ID).into_bytes();
+
+			let weight = PropertyDefinition { name: WEIGHT_PROP.to_vec(), data_type: DataType::Number, ..Default::default() };
+			let properties = vec![
+				struct_property(b"level1", vec![
+					struct_property(b"level2", vec![
+						struct_property(b"level3", vec![weight.clone()]),
+					]),
+				]),
+				// Property names only need to be unique among siblings
+				weight,
+			];
+
+			let admin_role_id = store_admin_role(owner.clone());
+			store_test_org(owner.clone(), String::from(TEST_ORG_NAME).into_bytes());
+			store_test_agent(sender, owner.clone(), true, vec![admin_role_id]);
+
+			assert_ok!(GridSchema::create_schema(Origin::signed(sender), schema, owner, properties));
+		})
+	}
+
+	#[test]
+	fn create_schema_with_missing_property_name() {
+		assert_invalid_properties(
+			vec![PropertyDefinition::default()],
+			ERR_SCHEMA_PROPERTY_NAME_REQUIRED
+		);
+	}
+
+	#[test]
+	fn create_schema_with_long_property_name() {
+		assert_invalid_properties(
+			vec![PropertyDefinition { name: String::from(LONG_VALUE).into_bytes(), ..Default::default() }],
+			ERR_SCHEMA_PROPERTY_NAME_TOO_LONG
+		);
+	}
+
+	#[test]
+	fn create_schema_with_duplicate_property_name() {
+		assert_invalid_properties(
+			vec![
+				PropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::String, ..Default::default() },
+				PropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::Bytes, ..Default::default() },
+			],
+			ERR_SCHEMA_PROPERTY_NAME_DUPLICATED
+		);
+	}
+
+	#[test]
+	fn create_schema_with_enum_without_options() {
+		assert_invalid_properties(
+			vec![PropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::Enum, ..Default::default() }],
+			ERR_SCHEMA_ENUM_OPTIONS_REQUIRED
+		);
+	}
+
+	#[test]
+	fn create_schema_with_empty_struct() {
+		assert_invalid_properties(
+			vec![struct_property(LOCATION_PROP, vec!())],
+			ERR_SCHEMA_STRUCT_PROPERTIES_REQUIRED
+		);
+	}
+
+	#[test]
+	fn create_schema_with_invalid_nested_property() {
+		assert_invalid_properties(
+			vec![struct_property(LOCATION_PROP, vec![PropertyDefinition::default()])],
+			ERR_SCHEMA_PROPERTY_NAME_REQUIRED
+		);
+	}
+
+	#[test]
+	fn create_schema_with_too_deep_properties() {
+		let weight = PropertyDefinition { name: WEIGHT_PROP.to_vec(), data_type: DataType::Number, ..Default::default() };
+		assert_invalid_properties(
+			vec![
+				struct_property(b"level1", vec![
+					struct_property(b"level2", vec![
+						struct_property(b"level3", vec![
+							struct_property(b"level4", vec![weight]),
+						]),
+					]),
+				]),
+			],
+			ERR_SCHEMA_PROPERTIES_TOO_DEEP
+		);
+	}
+
+	#[test]
+	fn decode_properties_with_bounded_nesting() {
+		let weight = PropertyDefinition { name: WEIGHT_PROP.to_vec(), data_type: DataType::Number, ..Default::default() };
+		let mut property = struct_property(b"level4", vec![weight]);
+		for _ in 0..3 {
+			property = struct_property(b"level", vec![property]);
+		}
+		// Too deep to be valid, but still decoded so that validation reports it
+		let encoded = vec![property.clone()].encode();
+		assert_eq!(Vec::<PropertyDefinition>::decode(&mut &encoded[..]), Some(vec![property.clone()]));
+
+		let too_deep = vec![struct_property(b"level0", vec![property])].encode();
+		assert_eq!(Vec::<PropertyDefinition>::decode(&mut &too_deep[..]), None);
+	}
+
+	#[test]
+	fn decode_values_with_bounded_nesting() {
+		let mut nested = Value::Boolean(true);
+		for _ in 0..DECODE_DEPTH_LIMIT {
+			nested = Value::Struct(vec![PropertyValue { name: b"level".to_vec(), value: nested }]);
+		}
+		let encoded = nested.encode();
+		assert_eq!(Value::decode(&mut &encoded[..]), Some(nested.clone()));
+
+		let too_deep = Value::Struct(vec![PropertyValue { name: b"level".to_vec(), value: nested }]).encode();
+		assert_eq!(Value::decode(&mut &too_deep[..]), None);
+
+		// Other variants keep their encoding
+		let values = vec![
+			Value::Number(-12_500, -3),
+			Value::LatLong(48_856_614, 2_352_222),
+			Value::OffchainRef(OffchainRef::of(HashAlgorithm::Keccak256, b"<jpeg>", None)),
+		];
+		let encoded = values.encode();
+		assert_eq!(Vec::<Value>::decode(&mut &encoded[..]), Some(values));
+	}
+
+	// property values validation tests
+	const STATUS_PROP: &[u8] = b"status";
+
+	fn value_definitions() -> Vec<PropertyDefinition> {
+		vec![
+			PropertyDefinition { name: TYPE_PROP.to_vec(), data_type: DataType::String, required: true, ..Default::default() },
+			PropertyDefinition { name: WEIGHT_PROP.to_vec(), data_type: DataType::Number, number_exponent: -3, ..Default::default() },
+			PropertyDefinition {
+				name: STATUS_PROP.to_vec(),
+				data_type: DataType::Enum,
+				enum_options: vec![b"ok".to_vec(), b"damaged".to_vec()],
+				..Default::default()
+			},
+			struct_property(b"package", vec![
+				PropertyDefinition { name: b"fragile".to_vec(), data_type: DataType::Boolean, required: true, ..Default::default() },
+			]),
+			PropertyDefinition { name: LOCATION_PROP.to_vec(), data_type: DataType::LatLong, ..Default::default() },
+			PropertyDefinition { name: IMAGE_PROP.to_vec(), data_type: DataType::OffchainRef, ..Default::default() },
+		]
+	}
+
+	fn value(name: &[u8], value: Value) -> PropertyValue {
+		PropertyValue { name: name.to_vec(), value }
+	}
+
+	fn type_value() -> PropertyValue {
+		value(TYPE_PROP, Value::String(b"pallet".to_vec()))
+	}
+
+	fn assert_invalid_values(values: Vec<PropertyValue>, err: &'static str) {
+		with_externalities(&mut build_ext(), || {
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+			store_test_schema_with_properties(
+				schema.clone(), String::from(TEST_ORG_ID).into_bytes(), value_definitions());
+
+			assert_eq!(GridSchema::validate_values(&schema, &values), Err(err));
+		})
+	}
+
+	#[test]
+	fn validate_values_with_valid_values() {
+		with_externalities(&mut build_ext(), || {
+			let schema = String::from(TEST_SCHEMA_NAME).into_bytes();
+			store_test_schema_with_properties(
+				schema.clone(), String::from(TEST_ORG_ID).into_bytes(), value_definitions());
+
+			let values = vec![
+				value(LOCATION_PROP, Value::LatLong(48_856_614, -180_000_000)),
+				type_value(),
+				value(WEIGHT_PROP, Value::Number(12_500, -3)),
+				value(STATUS_PROP, Value::Enum(1)),
+				value(b"package", Value::Struct(vec![value(b"fragile", Value::Boolean(true))])),
+				value(IMAGE_PROP, Value::OffchainRef(
+					OffchainRef::of(HashAlgorithm::Blake2_256, b"<jpeg>", Some(b"ipfs://bafkreigh2akiscaildcqabsyg3dfr6chu3fgpregiymsck7e7aqa4s52zy".to_vec())))),
+			];
+			assert_ok!(GridSchema::validate_values(&schema, &values));
+
+			// Optional properties may be omitted
+			assert_ok!(GridSchema::validate_values(&schema, &[type_value()]));
+		})
+	}
+
+	#[test]
+	fn validate_values_with_unknown_schema() {
+		with_externalities(&mut build_ext(), || {
+			assert_eq!(
+				GridSchema::validate_values(TEST_SCHEMA_NAME.as_bytes(), &[type_value()]),
+				Err(ERR_SCHEMA_DOES_NOT_EXIST)
+			);
+		})
+	}
+
+	#[test]
+	fn validate_values_with_missing_required_value() {
+		assert_invalid_values(
+			vec![value(WEIGHT_PROP, Value::Number(12_500, -3))],
+			ERR_PROPERTY_VALUE_REQUIRED
+		);
+	}
+
+	#[test]
+	fn validate_values_with_missing_nested_required_value() {
+		assert_invalid_values(
+			vec![type_value(), value(b"package", Value::Struct(vec!()))],
+			ERR_PROPERTY_VALUE_REQUIRED
+		);
+	}
+
+	#[test]
+	fn validate_values_with_undefined_value() {
+		assert_invalid_values(
+			vec![type_value(), value(TEMP_PROP, Value::Number(20, 0))],
+			ERR_PROPERTY_VALUE_UNDEFINED
+		);
+	}
+
+	#[test]
+	fn validate_values_with_duplicate_value() {
+		assert_invalid_values(
+			vec![type_value(), type_value()],
+			ERR_PROPERTY_VALUE_DUPLICATED
+		);
+	}
+
+	#[test]
+	fn validate_values_with_type_mismatch() {
+		assert_invalid_values(
+			vec![value(TYPE_PROP, Value::Bytes(b"pallet".to_vec()))],
+			ERR_PROPERTY_VALUE_TYPE_MISMATCH
+		);
+	}
+
+	#[test]
+	fn validate_values_with_exponent_mismatch() {
+		assert_invalid_values(
+			vec![type_value(), value(WEIGHT_PROP, Value::Number(12_500, -2))],
+			ERR_PROPERTY_VALUE_EXPONENT_MISMATCH
+		);
+	}
+
+	#[test]
+	fn validate_values_with_enum_out_of_range() {
+		assert_invalid_values(
+			vec![type_value(), value(STATUS_PROP, Value::Enum(2))],
+			ERR_PROPERTY_VALUE_ENUM_OUT_OF_RANGE
+		);
+	}
+
+	#[test]
+	fn validate_values_with_latlong_out_of_bounds() {
+		assert_invalid_values(
+			vec![type_value(), value(LOCATION_PROP, Value::LatLong(90_000_001, 0))],
+			ERR_PROPERTY_VALUE_LATLONG_OUT_OF_BOUNDS
+		);
+		assert_invalid_values(
+			vec![type_value(), value(LOCATION_PROP, Value::LatLong(0, -180_000_001))],
+			ERR_PROPERTY_VALUE_LATLONG_OUT_OF_BOUNDS
+		);
+		// abs() of these would overflow
+		assert_invalid_values(
+			vec![type_value(), value(LOCATION_PROP, Value::LatLong(i64::min_value(), 0))],
+			ERR_PROPERTY_VALUE_LATLONG_OUT_OF_BOUNDS
+		);
+		assert_invalid_values(
+			vec![type_value(), value(LOCATION_PROP, Value::LatLong(0, i64::min_value()))],
+			ERR_PROPERTY_VALUE_LATLONG_OUT_OF_BOUNDS
+		);
+	}
+
+	#[test]
+	fn validate_values_with_invalid_offchain_ref() {
+		assert_invalid_values(
+			vec![type_value(), value(IMAGE_PROP, Value::OffchainRef(
+				OffchainRef::of(HashAlgorithm::Blake2_256, b"<jpeg>", Some(vec!()))))],
+			ERR_OFFCHAIN_URI_REQUIRED
+		);
+		assert_invalid_values(
+			vec![type_value(), value(IMAGE_PROP, Value::Bytes(b"<jpeg>".to_vec()))],
+			ERR_PROPERTY_VALUE_TYPE_MISMATCH
+		);
+	}
+
+	#[test]
+	fn validate_partial_values_without_required_value() {
+		let definitions = value_definitions();
+		let weight = value(WEIGHT_PROP, Value::Number(12_500, -3));
+
+		assert_ok!(validate_partial_property_values(&definitions, &[weight.clone()]));
+		assert_eq!(
+			validate_partial_property_values(
+				&definitions, &[value(b"package", Value::Struct(vec!()))]),
+			Err(ERR_PROPERTY_VALUE_REQUIRED)
+		);
+		assert_eq!(
+			validate_partial_property_values(&definitions, &[weight.clone(), weight]),
+			Err(ERR_PROPERTY_VALUE_DUPLICATED)
+		);
+	}
 }
@@ -0,0 +1,1304 @@
+// Copyright 2019 Steve Degosserie
+// Hyperledger Grid Track & Trace compatible runtime module
+
+use crate::grid_pike::{BYTEARRAY_LIMIT, DESCRIPTION_LIMIT, OrgId};
+use crate::grid_pike::Module as PikeModule;
+use crate::grid_schema::{
+	Name, PropertyDefinition, PropertyValue, Value, ERR_SCHEMA_DOES_NOT_EXIST,
+	validate_property_values, validate_partial_property_values};
+use crate::grid_schema::Trait as SchemaTrait;
+use crate::grid_schema::Module as SchemaModule;
+use rstd::prelude::*;
+use parity_codec::{Decode, Encode};
+use runtime_primitives::traits::{Saturating, Zero};
+use support::{
+	decl_module, decl_storage, decl_event,
+	ensure, StorageMap,
+	dispatch::Result
+};
+use system::ensure_signed;
+
+pub const ERR_RECORD_ID_REQUIRED: &str = "Record ID required";
+pub const ERR_RECORD_ID_TOO_LONG: &str = "Record ID too long";
+pub const ERR_RECORD_ALREADY_EXISTS: &str = "Record already exists";
+pub const ERR_RECORD_DOES_NOT_EXIST: &str = "Record does not exist";
+pub const ERR_RECORD_IS_FINAL: &str = "Record is final and cannot be modified";
+pub const ERR_SENDER_MUST_BE_REPORTER: &str = "Sender must be the record custodian or an authorized reporter";
+pub const ERR_SENDER_MUST_BE_OWNER_OR_CUSTODIAN: &str = "Sender must be the record owner or custodian";
+pub const ERR_REPORTER_PROPERTY_UNDEFINED: &str = "Reporter property not defined in record schema";
+pub const ERR_REPORTER_NOT_AUTHORIZED: &str = "Reporter is not authorized for the property";
+pub const ERR_REPORTER_PROPERTIES_REQUIRED: &str = "Reporter proposal must name at least one property";
+pub const ERR_SENDER_MUST_BE_OWNER_AND_CUSTODIAN: &str = "Sender must be both record owner and custodian";
+pub const ERR_SENDER_MUST_HOLD_ROLE: &str = "Sender must hold the proposed role on the record";
+pub const ERR_RECEIVING_AGENT_DOES_NOT_EXIST: &str = "Receiving agent must be a known organization agent";
+pub const ERR_RECEIVING_AGENT_HOLDS_ROLE: &str = "Receiving agent already holds the proposed role";
+pub const ERR_PROPOSAL_TERMS_TOO_LONG: &str = "Proposal terms too long";
+pub const ERR_PROPOSAL_ALREADY_EXISTS: &str = "An open proposal already exists for this agent and role";
+pub const ERR_PROPOSAL_DOES_NOT_EXIST: &str = "No open proposal for this agent and role";
+pub const ERR_PROPOSAL_EXPIRED: &str = "Proposal has expired";
+pub const ERR_PROPOSAL_ISSUER_NO_LONGER_HOLDS_ROLE: &str = "Proposal issuer no longer holds the proposed role";
+pub const ERR_SENDER_MUST_BE_RECEIVING_AGENT: &str = "Sender must be the proposal receiving agent";
+pub const ERR_SENDER_MUST_BE_ISSUING_AGENT: &str = "Sender must be the proposal issuing agent";
+
+pub const PERM_CAN_CREATE_RECORD: &[u8] = b"track::can-create-record";
+
+pub type RecordId = Vec<u8>;
+
+fn validate_record_id(record_id: &[u8]) -> Result {
+	ensure!(record_id.len() > 0, ERR_RECORD_ID_REQUIRED);
+	ensure!(record_id.len() <= BYTEARRAY_LIMIT, ERR_RECORD_ID_TOO_LONG);
+	Ok(())
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct Record<AccountId> {
+	pub record_id: RecordId,
+	// Index of the record schema
+	pub schema: u32,
+	// Schema version the record properties are validated against
+	pub schema_version: u32,
+	pub owner: AccountId,
+	pub custodian: AccountId,
+	pub finalized: bool,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct ReportedValue<AccountId, Moment> {
+	pub reporter: AccountId,
+	pub value: Value,
+	pub timestamp: Moment,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Reporter<AccountId, BlockNumber> {
+	pub account: AccountId,
+	// Revoked reporters are kept, for auditing past reported values
+	pub authorized: bool,
+	pub authorized_at: BlockNumber,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum ProposalRole {
+	Owner,
+	Custodian,
+	// Authorization to report values for the named properties
+	Reporter(Vec<Name>),
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum ProposalStatus {
+	Open,
+	Accepted,
+	Rejected,
+	Canceled,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum ProposalResponse {
+	Accept,
+	Reject,
+	Cancel,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Proposal<AccountId, BlockNumber> {
+	pub issuing_agent: AccountId,
+	pub receiving_agent: AccountId,
+	pub role: ProposalRole,
+	pub status: ProposalStatus,
+	pub terms: Vec<u8>,
+	pub created_at: BlockNumber,
+	// None when proposals are configured to never expire
+	pub expires_at: Option<BlockNumber>,
+}
+
+#[derive(Default)]
+pub struct RecordBuilder<AccountId> {
+	record_id: RecordId,
+	schema: u32,
+	schema_version: u32,
+	owner: AccountId,
+	custodian: AccountId,
+}
+impl<AccountId> RecordBuilder<AccountId>
+	where AccountId: Default {
+	pub fn with_id(mut self, record_id: RecordId) -> Self {
+		self.record_id = record_id;
+		self
+	}
+
+	pub fn with_schema(mut self, schema: u32, schema_version: u32) -> Self {
+		self.schema = schema;
+		self.schema_version = schema_version;
+		self
+	}
+
+	pub fn with_owner(mut self, owner: AccountId) -> Self {
+		self.owner = owner;
+		self
+	}
+
+	pub fn with_custodian(mut self, custodian: AccountId) -> Self {
+		self.custodian = custodian;
+		self
+	}
+
+	pub fn build(self) -> rstd::result::Result<Record<AccountId>, &'static str> {
+		validate_record_id(&self.record_id)?;
+		let mut record = Record::default();
+		record.record_id = self.record_id;
+		record.schema = self.schema;
+		record.schema_version = self.schema_version;
+		record.owner = self.owner;
+		record.custodian = self.custodian;
+		Ok(record)
+	}
+}
+
+pub trait Trait: SchemaTrait + timestamp::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as GridTrackAndTrace {
+		Records get(record_by_id): map RecordId => Option<Record<T::AccountId>>;
+		// Time-series of the values reported for each record property, indexed in order of report
+		ReportedValues get(reported_value_by_index):
+			map (RecordId, Name, u32) => Option<ReportedValue<T::AccountId, T::Moment>>;
+		ReportedValuesCount get(reported_values_count): map (RecordId, Name) => u32;
+		// Accounts allowed to report values for each record property, besides the custodian
+		Reporters get(reporters): map (RecordId, Name) => Vec<Reporter<T::AccountId, T::BlockNumber>>;
+		// Every proposal ever made on a record, answered ones included, indexed in order of creation
+		Proposals get(proposal_by_index): map (RecordId, u32) => Option<Proposal<T::AccountId, T::BlockNumber>>;
+		ProposalsCount get(proposals_count): map RecordId => u32;
+		// Index of the latest open proposal made to an agent for a role, expired or not
+		OpenProposals get(open_proposal_index): map (RecordId, T::AccountId, ProposalRole) => Option<u32>;
+		// Number of blocks after which an open proposal expires, 0 to never expire
+		ProposalLifetime get(proposal_lifetime) config(): T::BlockNumber;
+	}
+}
+
+decl_event!(
+	pub enum Event<T>
+	where <T as system::Trait>::AccountId
+	{
+		RecordCreated(RecordId, AccountId),
+		PropertiesUpdated(RecordId, AccountId),
+		RecordFinalized(RecordId, AccountId),
+		ReporterAuthorized(RecordId, AccountId),
+		ReporterRevoked(RecordId, AccountId),
+		ProposalCreated(RecordId, AccountId, ProposalRole),
+		ProposalAnswered(RecordId, AccountId, ProposalRole, ProposalStatus),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event<T>() = default;
+
+		/// Creates a record against the current version of a schema, on behalf of an organization.
+		/// The sender becomes both owner and custodian of the record.
+		pub fn create_record(
+			origin, org_id: OrgId, record_id: RecordId, schema: Name,
+			properties: Vec<PropertyValue>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let (current, definitions) = <SchemaModule<T>>::usable_schema(&schema)?;
+			validate_record_id(&record_id)?;
+			Self::validate_new_record(&record_id)?;
+			validate_property_values(&definitions, &properties)?;
+
+			// Validate signer is an active agent of the specified org,
+			// with the right to create record
+			<PikeModule<T>>::ensure_permission(&sender, org_id, PERM_CAN_CREATE_RECORD)?;
+
+			let record = RecordBuilder::default()
+				.with_id(record_id.clone())
+				.with_schema(<SchemaModule<T>>::index_schema(&schema)?, current.version)
+				.with_owner(sender.clone())
+				.with_custodian(sender.clone())
+				.build()?;
+			<Records<T>>::insert(&record_id, record);
+			Self::report_values(&record_id, &sender, properties)?;
+			<SchemaModule<T>>::warn_if_deprecated(&current.name);
+
+			Self::deposit_event(RawEvent::RecordCreated(record_id, sender));
+
+			Ok(())
+		}
+
+		/// Reports new values for some of the record properties.
+		/// The custodian may report any property, other accounts only those they are authorized for.
+		pub fn update_properties(
+			origin, record_id: RecordId,
+			properties: Vec<PropertyValue>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let record = Self::validate_existing_record(&record_id)?;
+			ensure!(!record.finalized, ERR_RECORD_IS_FINAL);
+			if record.custodian != sender {
+				ensure!(
+					properties.iter().all(|p| Self::is_authorized_reporter(&record_id, &p.name, &sender)),
+					ERR_SENDER_MUST_BE_REPORTER
+				);
+			}
+			let definitions = Self::record_definitions(&record)?;
+			validate_partial_property_values(&definitions, &properties)?;
+			let schema = <SchemaModule<T>>::schema_by_index(record.schema).ok_or(ERR_SCHEMA_DOES_NOT_EXIST)?;
+
+			Self::report_values(&record_id, &sender, properties)?;
+			<SchemaModule<T>>::warn_if_deprecated(&schema.name);
+
+			Self::deposit_event(RawEvent::PropertiesUpdated(record_id, sender));
+
+			Ok(())
+		}
+
+		/// Marks a record as final, after which it can no longer be updated.
+		pub fn finalize_record(origin, record_id: RecordId) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let mut record = Self::validate_existing_record(&record_id)?;
+			ensure!(!record.finalized, ERR_RECORD_IS_FINAL);
+			ensure!(
+				record.owner == sender && record.custodian == sender,
+				ERR_SENDER_MUST_BE_OWNER_AND_CUSTODIAN
+			);
+
+			record.finalized = true;
+			<Records<T>>::insert(&record_id, record);
+
+			Self::deposit_event(RawEvent::RecordFinalized(record_id, sender));
+
+			Ok(())
+		}
+
+		/// Authorizes an account to report values for some of the record properties.
+		pub fn authorize_reporter(
+			origin, record_id: RecordId, reporter: T::AccountId,
+			properties: Vec<Name>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let record = Self::validate_existing_record(&record_id)?;
+			ensure!(!record.finalized, ERR_RECORD_IS_FINAL);
+			ensure!(
+				record.owner == sender || record.custodian == sender,
+				ERR_SENDER_MUST_BE_OWNER_OR_CUSTODIAN
+			);
+			Self::validate_reporter_properties(&record, &properties)?;
+
+			Self::authorize(&record_id, &reporter, properties);
+
+			Self::deposit_event(RawEvent::ReporterAuthorized(record_id, reporter));
+
+			Ok(())
+		}
+
+		/// Revokes the authorization of an account to report values for some of the record properties.
+		pub fn revoke_reporter(
+			origin, record_id: RecordId, reporter: T::AccountId,
+			properties: Vec<Name>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let record = Self::validate_existing_record(&record_id)?;
+			ensure!(!record.finalized, ERR_RECORD_IS_FINAL);
+			ensure!(
+				record.owner == sender || record.custodian == sender,
+				ERR_SENDER_MUST_BE_OWNER_OR_CUSTODIAN
+			);
+			ensure!(
+				properties.iter().all(|name| Self::is_authorized_reporter(&record_id, name, &reporter)),
+				ERR_REPORTER_NOT_AUTHORIZED
+			);
+
+			for name in properties {
+				<Reporters<T>>::mutate((record_id.clone(), name), |reporters| {
+					for r in reporters.iter_mut().filter(|r| r.account == reporter) {
+						r.authorized = false;
+					}
+				});
+			}
+
+			Self::deposit_event(RawEvent::ReporterRevoked(record_id, reporter));
+
+			Ok(())
+		}
+
+		/// Proposes to transfer the sender's role on a record to another agent,
+		/// or to authorize another agent as reporter of some of the record properties.
+		pub fn create_proposal(
+			origin, record_id: RecordId, receiving_agent: T::AccountId,
+			role: ProposalRole, terms: Vec<u8>) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(terms.len() <= DESCRIPTION_LIMIT, ERR_PROPOSAL_TERMS_TOO_LONG);
+			let record = Self::validate_existing_record(&record_id)?;
+			ensure!(!record.finalized, ERR_RECORD_IS_FINAL);
+			if let ProposalRole::Reporter(properties) = &role {
+				ensure!(properties.len() > 0, ERR_REPORTER_PROPERTIES_REQUIRED);
+				ensure!(Self::can_grant_role(&record, &sender, &role), ERR_SENDER_MUST_BE_OWNER_OR_CUSTODIAN);
+				Self::validate_reporter_properties(&record, properties)?;
+			} else {
+				ensure!(Self::can_grant_role(&record, &sender, &role), ERR_SENDER_MUST_HOLD_ROLE);
+			}
+			ensure!(!Self::holds_role(&record, &receiving_agent, &role), ERR_RECEIVING_AGENT_HOLDS_ROLE);
+			ensure!(
+				<PikeModule<T>>::is_agent(&receiving_agent),
+				ERR_RECEIVING_AGENT_DOES_NOT_EXIST
+			);
+
+			let now = <system::Module<T>>::block_number();
+			let open_key = (record_id.clone(), receiving_agent.clone(), role.clone());
+			// Expired proposals are superseded by new ones
+			ensure!(
+				!Self::find_open_proposal(&open_key).map_or(false, |(_, p)| !Self::is_expired_proposal(&p, now)),
+				ERR_PROPOSAL_ALREADY_EXISTS
+			);
+			let count = Self::proposals_count(&record_id);
+			let new_count = count.checked_add(1)
+				.ok_or("Overflow adding a new proposal")?;
+
+			let lifetime = Self::proposal_lifetime();
+			let expires_at = if lifetime.is_zero() { None } else { Some(now.saturating_add(lifetime)) };
+			<Proposals<T>>::insert((record_id.clone(), count), Proposal {
+				issuing_agent: sender,
+				receiving_agent: receiving_agent.clone(),
+				role: role.clone(),
+				status: ProposalStatus::Open,
+				terms,
+				created_at: now,
+				expires_at,
+			});
+			<ProposalsCount<T>>::insert(&record_id, new_count);
+			<OpenProposals<T>>::insert(open_key, count);
+
+			Self::deposit_event(RawEvent::ProposalCreated(record_id, receiving_agent, role));
+
+			Ok(())
+		}
+
+		/// Answers the open proposal made to an agent for a role.
+		/// The receiving agent accepts or rejects it, the issuing agent may cancel it.
+		pub fn answer_proposal(
+			origin, record_id: RecordId, receiving_agent: T::AccountId,
+			role: ProposalRole, response: ProposalResponse) -> Result {
+			let sender = ensure_signed(origin)?;
+
+			let mut record = Self::validate_existing_record(&record_id)?;
+			let now = <system::Module<T>>::block_number();
+			let open_key = (record_id.clone(), receiving_agent.clone(), role.clone());
+			let (index, mut proposal) = Self::find_open_proposal(&open_key)
+				.ok_or(ERR_PROPOSAL_DOES_NOT_EXIST)?;
+
+			match response {
+				ProposalResponse::Accept => {
+					ensure!(sender == proposal.receiving_agent, ERR_SENDER_MUST_BE_RECEIVING_AGENT);
+					ensure!(!Self::is_expired_proposal(&proposal, now), ERR_PROPOSAL_EXPIRED);
+					ensure!(!record.finalized, ERR_RECORD_IS_FINAL);
+					// Another proposal for the same role may have been accepted since
+					ensure!(
+						Self::can_grant_role(&record, &proposal.issuing_agent, &role),
+						ERR_PROPOSAL_ISSUER_NO_LONGER_HOLDS_ROLE
+					);
+					match &role {
+						ProposalRole::Owner => record.owner = receiving_agent.clone(),
+						ProposalRole::Custodian => record.custodian = receiving_agent.clone(),
+						ProposalRole::Reporter(properties) => {
+							Self::authorize(&record_id, &receiving_agent, properties.clone());
+							Self::deposit_event(RawEvent::ReporterAuthorized(record_id.clone(), receiving_agent.clone()));
+						},
+					}
+					proposal.status = ProposalStatus::Accepted;
+					<Records<T>>::insert(&record_id, record);
+				},
+				ProposalResponse::Reject => {
+					ensure!(sender == proposal.receiving_agent, ERR_SENDER_MUST_BE_RECEIVING_AGENT);
+					proposal.status = ProposalStatus::Rejected;
+				},
+				ProposalResponse::Cancel => {
+					ensure!(sender == proposal.issuing_agent, ERR_SENDER_MUST_BE_ISSUING_AGENT);
+					proposal.status = ProposalStatus::Canceled;
+				},
+			}
+			let status = proposal.status.clone();
+			<Proposals<T>>::insert((record_id.clone(), index), proposal);
+			<OpenProposals<T>>::remove(open_key);
+
+			Self::deposit_event(RawEvent::ProposalAnswered(record_id, receiving_agent, role, status));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+
+	// Helpers
+	fn validate_new_record(record_id: &[u8]) -> Result {
+		ensure!(!<Records<T>>::exists(record_id.to_vec()), ERR_RECORD_ALREADY_EXISTS);
+		Ok(())
+	}
+
+	fn validate_existing_record(record_id: &[u8])
+		-> rstd::result::Result<Record<T::AccountId>, &'static str> {
+		<Records<T>>::get(record_id.to_vec()).ok_or(ERR_RECORD_DOES_NOT_EXIST)
+	}
+
+	pub fn is_authorized_reporter(record_id: &[u8], property: &[u8], account: &T::AccountId) -> bool {
+		<Reporters<T>>::get((record_id.to_vec(), property.to_vec()))
+			.iter()
+			.any(|r| r.account == *account && r.authorized)
+	}
+
+	fn holds_role(record: &Record<T::AccountId>, account: &T::AccountId, role: &ProposalRole) -> bool {
+		match role {
+			ProposalRole::Owner => record.owner == *account,
+			ProposalRole::Custodian => record.custodian == *account,
+			ProposalRole::Reporter(properties) =>
+				properties.iter().all(|name| Self::is_authorized_reporter(&record.record_id, name, account)),
+		}
+	}
+
+	/// Whether an account may propose a role to another agent.
+	/// Owner and custodian roles are transferred by their holder,
+	/// reporters are authorized by either the owner or the custodian.
+	fn can_grant_role(record: &Record<T::AccountId>, account: &T::AccountId, role: &ProposalRole) -> bool {
+		match role {
+			ProposalRole::Reporter(_) => record.owner == *account || record.custodian == *account,
+			_ => Self::holds_role(record, account, role),
+		}
+	}
+
+	fn validate_reporter_properties(record: &Record<T::AccountId>, properties: &[Name]) -> Result {
+		let definitions = Self::record_definitions(record)?;
+		ensure!(
+			properties.iter().all(|name| definitions.iter().any(|d| d.name == *name)),
+			ERR_REPORTER_PROPERTY_UNDEFINED
+		);
+		Ok(())
+	}
+
+	fn is_expired_proposal(proposal: &Proposal<T::AccountId, T::BlockNumber>, now: T::BlockNumber) -> bool {
+		proposal.expires_at.map_or(false, |expires_at| now > expires_at)
+	}
+
+	/// Finds the latest open proposal made to an agent for a role, along with its index.
+	/// Expired proposals can still be found, so that they can be rejected or canceled.
+	fn find_open_proposal(open_key: &(RecordId, T::AccountId, ProposalRole))
+		-> Option<(u32, Proposal<T::AccountId, T::BlockNumber>)> {
+		Self::open_proposal_index(open_key)
+			.and_then(|index| Self::proposal_by_index((open_key.0.clone(), index)).map(|p| (index, p)))
+	}
+
+	fn record_definitions(record: &Record<T::AccountId>)
+		-> rstd::result::Result<Vec<PropertyDefinition>, &'static str> {
+		<SchemaModule<T>>::schema_version_by_index((record.schema, record.schema_version))
+			.ok_or(ERR_SCHEMA_DOES_NOT_EXIST)
+	}
+
+	// PRIVATE MUTABLES
+	fn authorize(record_id: &[u8], reporter: &T::AccountId, properties: Vec<Name>) {
+		let now = <system::Module<T>>::block_number();
+		for name in properties {
+			<Reporters<T>>::mutate((record_id.to_vec(), name), |reporters| {
+				let authorized = Reporter { account: reporter.clone(), authorized: true, authorized_at: now };
+				match reporters.iter().position(|r| r.account == *reporter) {
+					Some(index) => reporters[index] = authorized,
+					None => reporters.push(authorized),
+				}
+			});
+		}
+	}
+
+	fn report_values(record_id: &[u8], reporter: &T::AccountId, properties: Vec<PropertyValue>) -> Result {
+		let timestamp = <timestamp::Module<T>>::now();
+		for property in properties {
+			let key = (record_id.to_vec(), property.name);
+			let count = Self::reported_values_count(&key);
+			let new_count = count.checked_add(1)
+				.ok_or("Overflow reporting a new value")?;
+
+			<ReportedValues<T>>::insert(
+				(key.0.clone(), key.1.clone(), count),
+				ReportedValue {
+					reporter: reporter.clone(),
+					value: property.value,
+					timestamp: timestamp.clone()
+				}
+			);
+			<ReportedValuesCount<T>>::insert(key, new_count);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::grid_pike::{ERR_SENDER_MISSING_PERMISSION, ERR_SENDER_MUST_BE_ORG_AGENT};
+	use crate::grid_pike::Trait as PikeTrait;
+	use crate::grid_pike::tests::{store_test_agent, store_test_role};
+	use crate::grid_gs1::tests::{
+		TEST_EXISTING_ORG, org, store_test_admin, store_other_admin};
+	use crate::grid_schema::{
+		DataType, SchemaStatus, ERR_PROPERTY_VALUE_REQUIRED, ERR_PROPERTY_VALUE_TYPE_MISMATCH, ERR_SCHEMA_RETIRED};
+	use crate::grid_schema::tests::set_test_schema_status;
+
+	use primitives::{Blake2Hasher, H256};
+	use runtime_io::with_externalities;
+	use runtime_primitives::{
+		testing::{Digest, DigestItem, Header},
+		traits::{BlakeTwo256, IdentityLookup},
+		BuildStorage,
+	};
+	use support::{assert_noop, assert_ok, impl_outer_origin, StorageValue};
+
+	impl_outer_origin! {
+		pub enum Origin for GridTrackAndTraceTest {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct GridTrackAndTraceTest;
+
+	impl system::Trait for GridTrackAndTraceTest {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type Digest = Digest;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type Log = DigestItem;
+	}
+	impl timestamp::Trait for GridTrackAndTraceTest {
+		type Moment = u64;
+		type OnTimestampSet = ();
+	}
+	impl PikeTrait for GridTrackAndTraceTest {
+		type Event = ();
+	}
+	impl SchemaTrait for GridTrackAndTraceTest {
+		type Event = ();
+	}
+	impl Trait for GridTrackAndTraceTest {
+		type Event = ();
+	}
+
+	type GridTrackAndTrace = super::Module<GridTrackAndTraceTest>;
+	type Timestamp = timestamp::Module<GridTrackAndTraceTest>;
+	type System = system::Module<GridTrackAndTraceTest>;
+
+	fn build_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let t = system::GenesisConfig::<GridTrackAndTraceTest>::default()
+			.build_storage()
+			.unwrap()
+			.0;
+		t.into()
+	}
+
+	const TEST_SCHEMA_NAME: &[u8] = b"shipment";
+	const TEST_RECORD_ID: &[u8] = b"shipment-0001";
+	const LONG_VALUE : &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Donec aliquam ut tortor nec congue. Pellente";
+
+	const TRACKING_PROP: &[u8] = b"tracking_number";
+	const TEMP_PROP: &[u8] = b"temperature";
+
+	fn value(name: &[u8], value: Value) -> PropertyValue {
+		PropertyValue { name: name.to_vec(), value }
+	}
+
+	fn reported_values(property: &[u8]) -> Vec<ReportedValue<u64, u64>> {
+		let key = (TEST_RECORD_ID.to_vec(), property.to_vec());
+		(0..GridTrackAndTrace::reported_values_count(&key))
+			.filter_map(|i| GridTrackAndTrace::reported_value_by_index((key.0.clone(), key.1.clone(), i)))
+			.collect()
+	}
+
+	fn test_properties() -> Vec<PropertyValue> {
+		vec![value(TRACKING_PROP, Value::String(b"1Z999AA10123456784".to_vec()))]
+	}
+
+	/// Stores the test organization with an admin agent, and the shipment schema.
+	fn store_test_shipper(admin: u64) {
+		store_test_admin(admin, TEST_SCHEMA_NAME, vec![
+			PropertyDefinition { name: TRACKING_PROP.to_vec(), data_type: DataType::String, required: true, ..Default::default() },
+			PropertyDefinition { name: TEMP_PROP.to_vec(), data_type: DataType::Number, number_exponent: -1, ..Default::default() },
+		]);
+	}
+
+	fn test_schema_index() -> u32 {
+		SchemaModule::<GridTrackAndTraceTest>::schema_index(TEST_SCHEMA_NAME.to_vec())
+	}
+
+	fn store_test_record(owner: u64, custodian: u64) {
+		Records::<GridTrackAndTraceTest>::insert(
+			TEST_RECORD_ID.to_vec(),
+			Record {
+				record_id: TEST_RECORD_ID.to_vec(),
+				schema: test_schema_index(),
+				schema_version: 1,
+				owner,
+				custodian,
+				finalized: false,
+			},
+		);
+	}
+
+	// create_record tests
+	#[test]
+	fn create_record_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+			Timestamp::set_timestamp(42);
+
+			assert_ok!(GridTrackAndTrace::create_record(
+				Origin::signed(sender), org(), TEST_RECORD_ID.to_vec(), TEST_SCHEMA_NAME.to_vec(), test_properties()));
+
+			assert_eq!(
+				GridTrackAndTrace::record_by_id(TEST_RECORD_ID.to_vec()),
+				Some(Record {
+					record_id: TEST_RECORD_ID.to_vec(),
+					schema: test_schema_index(),
+					schema_version: 1,
+					owner: sender,
+					custodian: sender,
+					finalized: false,
+				})
+			);
+			assert_eq!(
+				reported_values(TRACKING_PROP),
+				vec![ReportedValue { reporter: sender, value: test_properties()[0].value.clone(), timestamp: 42 }]
+			);
+		})
+	}
+
+	#[test]
+	fn create_record_with_missing_id() {
+		with_externalities(&mut build_ext(), || {
+			store_test_shipper(1);
+			assert_noop!(
+				GridTrackAndTrace::create_record(
+					Origin::signed(1), org(), vec!(), TEST_SCHEMA_NAME.to_vec(), test_properties()),
+				ERR_RECORD_ID_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_record_with_long_id() {
+		with_externalities(&mut build_ext(), || {
+			store_test_shipper(1);
+			assert_noop!(
+				GridTrackAndTrace::create_record(
+					Origin::signed(1), org(), String::from(LONG_VALUE).into_bytes(),
+					TEST_SCHEMA_NAME.to_vec(), test_properties()),
+				ERR_RECORD_ID_TOO_LONG
+			);
+		})
+	}
+
+	#[test]
+	fn create_record_with_unknown_schema() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridTrackAndTrace::create_record(
+					Origin::signed(1), org(), TEST_RECORD_ID.to_vec(), TEST_SCHEMA_NAME.to_vec(), test_properties()),
+				ERR_SCHEMA_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn create_record_with_existing_id() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+			store_test_record(sender, sender);
+
+			assert_noop!(
+				GridTrackAndTrace::create_record(
+					Origin::signed(sender), org(), TEST_RECORD_ID.to_vec(), TEST_SCHEMA_NAME.to_vec(), test_properties()),
+				ERR_RECORD_ALREADY_EXISTS
+			);
+		})
+	}
+
+	#[test]
+	fn create_record_with_missing_required_property() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+
+			assert_noop!(
+				GridTrackAndTrace::create_record(
+					Origin::signed(sender), org(), TEST_RECORD_ID.to_vec(), TEST_SCHEMA_NAME.to_vec(), vec!()),
+				ERR_PROPERTY_VALUE_REQUIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_record_without_permission() {
+		with_externalities(&mut build_ext(), || {
+			let agent = 2;
+			store_test_shipper(1);
+			let org_id = org();
+			let role_id = store_test_role(org_id.clone(), b"reader".to_vec(), vec!());
+			store_test_agent(agent, org_id, true, vec![role_id]);
+
+			assert_noop!(
+				GridTrackAndTrace::create_record(
+					Origin::signed(agent), org(), TEST_RECORD_ID.to_vec(), TEST_SCHEMA_NAME.to_vec(), test_properties()),
+				ERR_SENDER_MISSING_PERMISSION
+			);
+		})
+	}
+
+	#[test]
+	fn create_record_with_retired_schema() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+			set_test_schema_status(TEST_SCHEMA_NAME, SchemaStatus::Retired);
+
+			assert_noop!(
+				GridTrackAndTrace::create_record(
+					Origin::signed(sender), org(), TEST_RECORD_ID.to_vec(), TEST_SCHEMA_NAME.to_vec(), test_properties()),
+				ERR_SCHEMA_RETIRED
+			);
+		})
+	}
+
+	#[test]
+	fn create_record_for_other_org() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+			store_other_admin(2);
+
+			assert_noop!(
+				GridTrackAndTrace::create_record(
+					Origin::signed(sender), String::from(TEST_EXISTING_ORG).into_bytes(),
+					TEST_RECORD_ID.to_vec(), TEST_SCHEMA_NAME.to_vec(), test_properties()),
+				ERR_SENDER_MUST_BE_ORG_AGENT
+			);
+		})
+	}
+
+	// update_properties tests
+	#[test]
+	fn update_properties_appends_reported_values() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+			store_test_record(sender, sender);
+
+			Timestamp::set_timestamp(10);
+			assert_ok!(GridTrackAndTrace::update_properties(
+				Origin::signed(sender), TEST_RECORD_ID.to_vec(), vec![value(TEMP_PROP, Value::Number(45, -1))]));
+			Timestamp::set_timestamp(20);
+			assert_ok!(GridTrackAndTrace::update_properties(
+				Origin::signed(sender), TEST_RECORD_ID.to_vec(), vec![value(TEMP_PROP, Value::Number(52, -1))]));
+
+			assert_eq!(
+				reported_values(TEMP_PROP),
+				vec![
+					ReportedValue { reporter: sender, value: Value::Number(45, -1), timestamp: 10 },
+					ReportedValue { reporter: sender, value: Value::Number(52, -1), timestamp: 20 },
+				]
+			);
+		})
+	}
+
+	#[test]
+	fn update_properties_with_deprecated_schema() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+			store_test_record(sender, sender);
+			set_test_schema_status(TEST_SCHEMA_NAME, SchemaStatus::Deprecated);
+
+			assert_ok!(GridTrackAndTrace::update_properties(
+				Origin::signed(sender), TEST_RECORD_ID.to_vec(), test_properties()));
+			assert_eq!(
+				reported_values(TRACKING_PROP).len(),
+				1
+			);
+		})
+	}
+
+	#[test]
+	fn update_properties_with_unknown_record() {
+		with_externalities(&mut build_ext(), || {
+			assert_noop!(
+				GridTrackAndTrace::update_properties(Origin::signed(1), TEST_RECORD_ID.to_vec(), vec!()),
+				ERR_RECORD_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn update_properties_from_non_custodian() {
+		with_externalities(&mut build_ext(), || {
+			let owner = 1;
+			let custodian = 2;
+			store_test_shipper(owner);
+			store_test_record(owner, custodian);
+
+			assert_noop!(
+				GridTrackAndTrace::update_properties(
+					Origin::signed(owner), TEST_RECORD_ID.to_vec(), vec![value(TEMP_PROP, Value::Number(45, -1))]),
+				ERR_SENDER_MUST_BE_REPORTER
+			);
+		})
+	}
+
+	#[test]
+	fn update_properties_with_invalid_value() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+			store_test_record(sender, sender);
+
+			assert_noop!(
+				GridTrackAndTrace::update_properties(
+					Origin::signed(sender), TEST_RECORD_ID.to_vec(), vec![value(TEMP_PROP, Value::Boolean(true))]),
+				ERR_PROPERTY_VALUE_TYPE_MISMATCH
+			);
+		})
+	}
+
+	// reporters tests
+	const SENSOR: u64 = 3;
+
+	fn temperature(tenths: i64) -> Vec<PropertyValue> {
+		vec![value(TEMP_PROP, Value::Number(tenths, -1))]
+	}
+
+	#[test]
+	fn authorize_reporter_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let owner = 1;
+			let custodian = 2;
+			store_test_shipper(owner);
+			store_test_record(owner, custodian);
+			System::set_block_number(7);
+
+			assert_ok!(GridTrackAndTrace::authorize_reporter(
+				Origin::signed(custodian), TEST_RECORD_ID.to_vec(), SENSOR, vec![TEMP_PROP.to_vec()]));
+
+			assert_eq!(
+				GridTrackAndTrace::reporters((TEST_RECORD_ID.to_vec(), TEMP_PROP.to_vec())),
+				vec![Reporter { account: SENSOR, authorized: true, authorized_at: 7 }]
+			);
+
+			Timestamp::set_timestamp(30);
+			assert_ok!(GridTrackAndTrace::update_properties(
+				Origin::signed(SENSOR), TEST_RECORD_ID.to_vec(), temperature(45)));
+			assert_eq!(
+				reported_values(TEMP_PROP),
+				vec![ReportedValue { reporter: SENSOR, value: Value::Number(45, -1), timestamp: 30 }]
+			);
+
+			// Authorization is per property
+			assert_noop!(
+				GridTrackAndTrace::update_properties(
+					Origin::signed(SENSOR), TEST_RECORD_ID.to_vec(), test_properties()),
+				ERR_SENDER_MUST_BE_REPORTER
+			);
+		})
+	}
+
+	#[test]
+	fn authorize_reporter_from_other_account() {
+		with_externalities(&mut build_ext(), || {
+			let owner = 1;
+			store_test_shipper(owner);
+			store_test_record(owner, owner);
+
+			assert_noop!(
+				GridTrackAndTrace::authorize_reporter(
+					Origin::signed(SENSOR), TEST_RECORD_ID.to_vec(), SENSOR, vec![TEMP_PROP.to_vec()]),
+				ERR_SENDER_MUST_BE_OWNER_OR_CUSTODIAN
+			);
+		})
+	}
+
+	#[test]
+	fn authorize_reporter_for_undefined_property() {
+		with_externalities(&mut build_ext(), || {
+			let owner = 1;
+			store_test_shipper(owner);
+			store_test_record(owner, owner);
+
+			assert_noop!(
+				GridTrackAndTrace::authorize_reporter(
+					Origin::signed(owner), TEST_RECORD_ID.to_vec(), SENSOR, vec![b"humidity".to_vec()]),
+				ERR_REPORTER_PROPERTY_UNDEFINED
+			);
+		})
+	}
+
+	#[test]
+	fn revoke_reporter_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let owner = 1;
+			store_test_shipper(owner);
+			store_test_record(owner, owner);
+			assert_ok!(GridTrackAndTrace::authorize_reporter(
+				Origin::signed(owner), TEST_RECORD_ID.to_vec(), SENSOR, vec![TEMP_PROP.to_vec()]));
+
+			assert_ok!(GridTrackAndTrace::revoke_reporter(
+				Origin::signed(owner), TEST_RECORD_ID.to_vec(), SENSOR, vec![TEMP_PROP.to_vec()]));
+
+			assert_eq!(
+				GridTrackAndTrace::reporters((TEST_RECORD_ID.to_vec(), TEMP_PROP.to_vec())),
+				vec![Reporter { account: SENSOR, authorized: false, authorized_at: 0 }]
+			);
+			assert_noop!(
+				GridTrackAndTrace::update_properties(
+					Origin::signed(SENSOR), TEST_RECORD_ID.to_vec(), temperature(45)),
+				ERR_SENDER_MUST_BE_REPORTER
+			);
+			assert_noop!(
+				GridTrackAndTrace::revoke_reporter(
+					Origin::signed(owner), TEST_RECORD_ID.to_vec(), SENSOR, vec![TEMP_PROP.to_vec()]),
+				ERR_REPORTER_NOT_AUTHORIZED
+			);
+		})
+	}
+
+	// finalize_record tests
+	#[test]
+	fn finalize_record_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			let sender = 1;
+			store_test_shipper(sender);
+			store_test_record(sender, sender);
+
+			assert_ok!(GridTrackAndTrace::finalize_record(Origin::signed(sender), TEST_RECORD_ID.to_vec()));
+			assert_eq!(
+				GridTrackAndTrace::record_by_id(TEST_RECORD_ID.to_vec()).map(|r| r.finalized),
+				Some(true)
+			);
+
+			assert_noop!(
+				GridTrackAndTrace::update_properties(
+					Origin::signed(sender), TEST_RECORD_ID.to_vec(), vec![value(TEMP_PROP, Value::Number(45, -1))]),
+				ERR_RECORD_IS_FINAL
+			);
+		})
+	}
+
+	#[test]
+	fn finalize_record_from_non_custodian_owner() {
+		with_externalities(&mut build_ext(), || {
+			let owner = 1;
+			let custodian = 2;
+			store_test_shipper(owner);
+			store_test_record(owner, custodian);
+
+			assert_noop!(
+				GridTrackAndTrace::finalize_record(Origin::signed(owner), TEST_RECORD_ID.to_vec()),
+				ERR_SENDER_MUST_BE_OWNER_AND_CUSTODIAN
+			);
+		})
+	}
+
+	// genesis config tests
+	#[test]
+	fn genesis_config_with_proposal_lifetime() {
+		let (mut t, mut c) = system::GenesisConfig::<GridTrackAndTraceTest>::default()
+			.build_storage()
+			.unwrap();
+		GenesisConfig::<GridTrackAndTraceTest> {
+			proposal_lifetime: 100,
+		}.assimilate_storage(&mut t, &mut c).unwrap();
+		let mut ext: runtime_io::TestExternalities<Blake2Hasher> = t.into();
+
+		with_externalities(&mut ext, || {
+			assert_eq!(GridTrackAndTrace::proposal_lifetime(), 100);
+		})
+	}
+
+	// proposals tests
+	const OWNER: u64 = 1;
+	const RECEIVER: u64 = 2;
+
+	fn setup_proposal() {
+		store_test_shipper(OWNER);
+		store_test_agent(RECEIVER, org(), true, vec!());
+		store_test_record(OWNER, OWNER);
+	}
+
+	fn propose(role: ProposalRole) -> Result {
+		GridTrackAndTrace::create_proposal(
+			Origin::signed(OWNER), TEST_RECORD_ID.to_vec(), RECEIVER, role, b"Net 30".to_vec())
+	}
+
+	fn proposals() -> Vec<Proposal<u64, u64>> {
+		(0..GridTrackAndTrace::proposals_count(TEST_RECORD_ID.to_vec()))
+			.filter_map(|i| GridTrackAndTrace::proposal_by_index((TEST_RECORD_ID.to_vec(), i)))
+			.collect()
+	}
+
+	fn answer(sender: u64, role: ProposalRole, response: ProposalResponse) -> Result {
+		GridTrackAndTrace::answer_proposal(
+			Origin::signed(sender), TEST_RECORD_ID.to_vec(), RECEIVER, role, response)
+	}
+
+	#[test]
+	fn create_proposal_with_valid_args() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			ProposalLifetime::<GridTrackAndTraceTest>::put(10);
+			System::set_block_number(5);
+
+			assert_ok!(propose(ProposalRole::Custodian));
+
+			assert_eq!(
+				proposals(),
+				vec![Proposal {
+					issuing_agent: OWNER,
+					receiving_agent: RECEIVER,
+					role: ProposalRole::Custodian,
+					status: ProposalStatus::Open,
+					terms: b"Net 30".to_vec(),
+					created_at: 5,
+					expires_at: Some(15),
+				}]
+			);
+		})
+	}
+
+	#[test]
+	fn create_proposal_for_role_not_held() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			store_test_record(OWNER, 3);
+
+			assert_noop!(propose(ProposalRole::Custodian), ERR_SENDER_MUST_HOLD_ROLE);
+		})
+	}
+
+	#[test]
+	fn create_proposal_to_unknown_agent() {
+		with_externalities(&mut build_ext(), || {
+			store_test_shipper(OWNER);
+			store_test_record(OWNER, OWNER);
+
+			assert_noop!(propose(ProposalRole::Owner), ERR_RECEIVING_AGENT_DOES_NOT_EXIST);
+		})
+	}
+
+	#[test]
+	fn create_proposal_twice() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			assert_ok!(propose(ProposalRole::Owner));
+
+			assert_noop!(propose(ProposalRole::Owner), ERR_PROPOSAL_ALREADY_EXISTS);
+			// A different role is a different proposal
+			assert_ok!(propose(ProposalRole::Custodian));
+		})
+	}
+
+	#[test]
+	fn accept_owner_proposal() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			assert_ok!(propose(ProposalRole::Owner));
+
+			assert_ok!(answer(RECEIVER, ProposalRole::Owner, ProposalResponse::Accept));
+
+			let record = GridTrackAndTrace::record_by_id(TEST_RECORD_ID.to_vec()).unwrap();
+			assert_eq!(record.owner, RECEIVER);
+			assert_eq!(record.custodian, OWNER);
+			assert_eq!(
+				proposals()[0].status,
+				ProposalStatus::Accepted
+			);
+		})
+	}
+
+	#[test]
+	fn accept_custodian_proposal() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			assert_ok!(propose(ProposalRole::Custodian));
+
+			assert_ok!(answer(RECEIVER, ProposalRole::Custodian, ProposalResponse::Accept));
+
+			let record = GridTrackAndTrace::record_by_id(TEST_RECORD_ID.to_vec()).unwrap();
+			assert_eq!(record.owner, OWNER);
+			assert_eq!(record.custodian, RECEIVER);
+		})
+	}
+
+	#[test]
+	fn accept_proposal_from_other_agent() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			assert_ok!(propose(ProposalRole::Owner));
+
+			assert_noop!(
+				answer(OWNER, ProposalRole::Owner, ProposalResponse::Accept),
+				ERR_SENDER_MUST_BE_RECEIVING_AGENT
+			);
+		})
+	}
+
+	#[test]
+	fn accept_expired_proposal() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			ProposalLifetime::<GridTrackAndTraceTest>::put(10);
+			System::set_block_number(1);
+			assert_ok!(propose(ProposalRole::Owner));
+
+			System::set_block_number(12);
+			assert_noop!(
+				answer(RECEIVER, ProposalRole::Owner, ProposalResponse::Accept),
+				ERR_PROPOSAL_EXPIRED
+			);
+
+			// Expired proposals are superseded by new ones
+			assert_ok!(propose(ProposalRole::Owner));
+			assert_ok!(answer(RECEIVER, ProposalRole::Owner, ProposalResponse::Accept));
+			assert_eq!(
+				proposals().iter().map(|p| p.status.clone()).collect::<Vec<_>>(),
+				vec![ProposalStatus::Open, ProposalStatus::Accepted]
+			);
+		})
+	}
+
+	#[test]
+	fn accept_proposal_after_role_transferred() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			let other = 3;
+			store_test_agent(other, org(), true, vec!());
+			assert_ok!(propose(ProposalRole::Owner));
+			assert_ok!(GridTrackAndTrace::create_proposal(
+				Origin::signed(OWNER), TEST_RECORD_ID.to_vec(), other, ProposalRole::Owner, vec!()));
+
+			assert_ok!(GridTrackAndTrace::answer_proposal(
+				Origin::signed(other), TEST_RECORD_ID.to_vec(), other, ProposalRole::Owner, ProposalResponse::Accept));
+
+			assert_noop!(
+				answer(RECEIVER, ProposalRole::Owner, ProposalResponse::Accept),
+				ERR_PROPOSAL_ISSUER_NO_LONGER_HOLDS_ROLE
+			);
+		})
+	}
+
+	#[test]
+	fn accept_reporter_proposal() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			store_test_record(OWNER, 3);
+			System::set_block_number(4);
+			let role = ProposalRole::Reporter(vec![TEMP_PROP.to_vec()]);
+			assert_ok!(propose(role.clone()));
+
+			assert_ok!(answer(RECEIVER, role.clone(), ProposalResponse::Accept));
+
+			assert_eq!(
+				GridTrackAndTrace::reporters((TEST_RECORD_ID.to_vec(), TEMP_PROP.to_vec())),
+				vec![Reporter { account: RECEIVER, authorized: true, authorized_at: 4 }]
+			);
+			// Owner and custodian are unchanged
+			let record = GridTrackAndTrace::record_by_id(TEST_RECORD_ID.to_vec()).unwrap();
+			assert_eq!((record.owner, record.custodian), (OWNER, 3));
+			assert_ok!(GridTrackAndTrace::update_properties(
+				Origin::signed(RECEIVER), TEST_RECORD_ID.to_vec(), temperature(45)));
+
+			assert_noop!(propose(role), ERR_RECEIVING_AGENT_HOLDS_ROLE);
+		})
+	}
+
+	#[test]
+	fn create_reporter_proposal_with_invalid_args() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+
+			assert_noop!(propose(ProposalRole::Reporter(vec!())), ERR_REPORTER_PROPERTIES_REQUIRED);
+			assert_noop!(
+				propose(ProposalRole::Reporter(vec![b"weight".to_vec()])),
+				ERR_REPORTER_PROPERTY_UNDEFINED
+			);
+
+			store_test_record(3, 3);
+			assert_noop!(
+				propose(ProposalRole::Reporter(vec![TEMP_PROP.to_vec()])),
+				ERR_SENDER_MUST_BE_OWNER_OR_CUSTODIAN
+			);
+		})
+	}
+
+	#[test]
+	fn create_proposal_with_lifetime_overflow() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			ProposalLifetime::<GridTrackAndTraceTest>::put(10);
+			System::set_block_number(u64::max_value() - 5);
+
+			assert_ok!(propose(ProposalRole::Owner));
+
+			assert_eq!(
+				proposals()[0].expires_at,
+				Some(u64::max_value())
+			);
+		})
+	}
+
+	#[test]
+	fn reject_proposal() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			assert_ok!(propose(ProposalRole::Owner));
+
+			assert_ok!(answer(RECEIVER, ProposalRole::Owner, ProposalResponse::Reject));
+
+			assert_eq!(
+				proposals()[0].status,
+				ProposalStatus::Rejected
+			);
+			assert_eq!(GridTrackAndTrace::record_by_id(TEST_RECORD_ID.to_vec()).unwrap().owner, OWNER);
+			assert_noop!(
+				answer(RECEIVER, ProposalRole::Owner, ProposalResponse::Accept),
+				ERR_PROPOSAL_DOES_NOT_EXIST
+			);
+		})
+	}
+
+	#[test]
+	fn cancel_proposal() {
+		with_externalities(&mut build_ext(), || {
+			setup_proposal();
+			assert_ok!(propose(ProposalRole::Owner));
+
+			assert_noop!(
+				answer(RECEIVER, ProposalRole::Owner, ProposalResponse::Cancel),
+				ERR_SENDER_MUST_BE_ISSUING_AGENT
+			);
+			assert_ok!(answer(OWNER, ProposalRole::Owner, ProposalResponse::Cancel));
+
+			assert_eq!(
+				proposals()[0].status,
+				ProposalStatus::Canceled
+			);
+		})
+	}
+}
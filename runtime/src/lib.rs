@@ -55,8 +55,16 @@ pub type BlockNumber = u64;
 /// Index of an account's extrinsic in the chain.
 pub type Nonce = u64;
 
-mod grid_pike;
-mod grid_schema;
+pub mod grid_pike;
+pub mod grid_schema;
+pub mod grid_product;
+pub mod grid_track_and_trace;
+pub mod grid_location;
+pub mod grid_gs1;
+pub mod grid_purchase_order;
+pub mod grid_offchain;
+pub mod grid_cid;
+pub mod grid_api;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
@@ -99,7 +107,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("grid"),
     impl_name: create_runtime_str!("grid"),
     authoring_version: 3,
-    spec_version: 3,
+    spec_version: 4,
     impl_version: 0,
     apis: RUNTIME_API_VERSIONS,
 };
@@ -199,6 +207,22 @@ impl grid_schema::Trait for Runtime {
     type Event = Event;
 }
 
+impl grid_product::Trait for Runtime {
+    type Event = Event;
+}
+
+impl grid_track_and_trace::Trait for Runtime {
+    type Event = Event;
+}
+
+impl grid_location::Trait for Runtime {
+    type Event = Event;
+}
+
+impl grid_purchase_order::Trait for Runtime {
+    type Event = Event;
+}
+
 construct_runtime!(
 	pub enum Runtime with Log(InternalLog: DigestItem<Hash, AuthorityId, AuthoritySignature>) where
 		Block = Block,
@@ -213,8 +237,12 @@ construct_runtime!(
 		Balances: balances,
 		Sudo: sudo,
         
-		GridPike: grid_pike::{Module, Call, Storage, Event<T>},
-		GridSchema: grid_schema::{Module, Call, Storage, Event},
+		GridPike: grid_pike::{Module, Call, Storage, Event<T>, Config<T>},
+		GridSchema: grid_schema::{Module, Call, Storage, Event, Config<T>},
+		GridProduct: grid_product::{Module, Call, Storage, Event},
+		GridTrackAndTrace: grid_track_and_trace::{Module, Call, Storage, Event<T>, Config<T>},
+		GridLocation: grid_location::{Module, Call, Storage, Event},
+		GridPurchaseOrder: grid_purchase_order::{Module, Call, Storage, Event},
 	}
 );
 
@@ -307,4 +335,48 @@ impl_runtime_apis! {
             Consensus::authorities()
         }
     }
+
+    impl grid_api::GridPikeApi<Block> for Runtime {
+        fn org(id: grid_pike::OrgId) -> Option<grid_pike::Organization> {
+            GridPike::org_by_id(&id)
+        }
+
+        fn agent(account: AccountId, org_id: grid_pike::OrgId) -> Option<grid_pike::Agent<AccountId>> {
+            GridPike::agent_of_org(&account, &org_id)
+        }
+
+        fn agents_of_account(account: AccountId) -> Vec<grid_pike::Agent<AccountId>> {
+            GridPike::agents_of_account(&account)
+        }
+
+        fn agents_of_org(id: grid_pike::OrgId) -> Vec<grid_pike::Agent<AccountId>> {
+            GridPike::agents_of_org(&id)
+        }
+
+        fn roles_of_agent(account: AccountId, org_id: grid_pike::OrgId) -> Vec<grid_pike::Role> {
+            GridPike::roles_of_agent(&account, &org_id)
+        }
+
+        fn is_admin(account: AccountId, org_id: grid_pike::OrgId) -> bool {
+            GridPike::is_admin(&account, org_id)
+        }
+
+        fn has_permission(account: AccountId, org_id: grid_pike::OrgId, permission: grid_pike::Permission) -> bool {
+            GridPike::has_permission(&account, org_id, &permission)
+        }
+    }
+
+    impl grid_api::GridSchemaApi<Block> for Runtime {
+        fn schema(name: grid_schema::Name) -> Option<(grid_schema::Schema, Vec<grid_schema::PropertyDefinition>)> {
+            GridSchema::current_schema(&name)
+        }
+
+        fn schemas_of_org(org_id: grid_pike::OrgId) -> Vec<grid_schema::Schema> {
+            GridSchema::schemas_of_org(&org_id)
+        }
+
+        fn validate_values(schema: grid_schema::Name, values: Vec<grid_schema::PropertyValue>) -> Result<(), Vec<u8>> {
+            GridSchema::validate_new_values(&schema, &values).map_err(|e| e.as_bytes().to_vec())
+        }
+    }
 }
@@ -1,12 +1,22 @@
 use primitives::{ed25519, sr25519, Pair};
 use grid_runtime::{
 	AccountId, GenesisConfig, ConsensusConfig, TimestampConfig, BalancesConfig,
-	SudoConfig, IndicesConfig,
+	SudoConfig, IndicesConfig, GridPikeConfig, GridSchemaConfig, GridTrackAndTraceConfig,
+};
+use grid_runtime::grid_pike::{OrgId, OrgName};
+use grid_runtime::grid_schema::{
+	PropertyDefinition, DataType, PERM_CAN_CREATE_SCHEMA, PERM_CAN_UPDATE_SCHEMA,
 };
 use substrate_service;
 
 use ed25519::Public as AuthorityId;
 
+/// The organization owning the consortium-wide schemas.
+const CONSORTIUM_ORG_ID: &[u8] = b"did:grid:consortium";
+
+/// Track & Trace proposals expire after a week of 10 second blocks.
+const PROPOSAL_LIFETIME: u64 = 7 * 24 * 60 * 6;
+
 // Note this is the URL for the telemetry server
 //const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
@@ -48,8 +58,10 @@ impl Alternative {
 				], vec![
 					account_key("Alice")
 				],
-					account_key("Alice")
-				),
+					account_key("Alice"),
+				vec![
+					(CONSORTIUM_ORG_ID.to_vec(), b"Grid Consortium".to_vec(), account_key("Alice")),
+				]),
 				vec![],
 				None,
 				None,
@@ -71,7 +83,11 @@ impl Alternative {
 					account_key("Ferdie"),
 				],
 					account_key("Alice"),
-				),
+				vec![
+					(CONSORTIUM_ORG_ID.to_vec(), b"Grid Consortium".to_vec(), account_key("Alice")),
+					(b"did:grid:manufacturer".to_vec(), b"Manufacturer".to_vec(), account_key("Bob")),
+					(b"did:grid:carrier".to_vec(), b"Carrier".to_vec(), account_key("Charlie")),
+				]),
 				vec![],
 				None,
 				None,
@@ -90,7 +106,22 @@ impl Alternative {
 	}
 }
 
-fn testnet_genesis(initial_authorities: Vec<AuthorityId>, endowed_accounts: Vec<AccountId>, root_key: AccountId) -> GenesisConfig {
+fn testnet_genesis(
+	initial_authorities: Vec<AuthorityId>,
+	endowed_accounts: Vec<AccountId>,
+	root_key: AccountId,
+	orgs: Vec<(OrgId, OrgName, AccountId)>,
+) -> GenesisConfig {
+	// Every organization can delegate schema management to its agents
+	let roles = orgs.iter()
+		.map(|(id, _, _)| (
+			id.clone(),
+			b"schema-editor".to_vec(),
+			b"Manages the organization's schemas".to_vec(),
+			vec![PERM_CAN_CREATE_SCHEMA.to_vec(), PERM_CAN_UPDATE_SCHEMA.to_vec()],
+		))
+		.collect();
+
 	GenesisConfig {
 		consensus: Some(ConsensusConfig {
 			code: include_bytes!("../runtime/wasm/target/wasm32-unknown-unknown/release/grid_runtime_wasm.compact.wasm").to_vec(),
@@ -115,5 +146,74 @@ fn testnet_genesis(initial_authorities: Vec<AuthorityId>, endowed_accounts: Vec<
 		sudo: Some(SudoConfig {
 			key: root_key,
 		}),
+		grid_pike: Some(GridPikeConfig {
+			orgs,
+			roles,
+		}),
+		grid_schema: Some(GridSchemaConfig {
+			schemas: vec![
+				(b"gs1_product".to_vec(), CONSORTIUM_ORG_ID.to_vec(), gs1_product_properties()),
+				(b"gs1_location".to_vec(), CONSORTIUM_ORG_ID.to_vec(), gs1_location_properties()),
+			],
+		}),
+		grid_track_and_trace: Some(GridTrackAndTraceConfig {
+			proposal_lifetime: PROPOSAL_LIFETIME,
+		}),
 	}
 }
+
+fn gs1_product_properties() -> Vec<PropertyDefinition> {
+	vec![
+		PropertyDefinition {
+			name: b"product_name".to_vec(),
+			data_type: DataType::String,
+			required: true,
+			..Default::default()
+		},
+		PropertyDefinition {
+			name: b"brand_name".to_vec(),
+			data_type: DataType::String,
+			..Default::default()
+		},
+		PropertyDefinition {
+			name: b"net_content".to_vec(),
+			data_type: DataType::Number,
+			description: b"Net content, in kilograms".to_vec(),
+			number_exponent: -3,
+			..Default::default()
+		},
+		PropertyDefinition {
+			name: b"target_market".to_vec(),
+			data_type: DataType::Number,
+			description: b"ISO 3166-1 numeric country code".to_vec(),
+			..Default::default()
+		},
+	]
+}
+
+fn gs1_location_properties() -> Vec<PropertyDefinition> {
+	vec![
+		PropertyDefinition {
+			name: b"location_name".to_vec(),
+			data_type: DataType::String,
+			required: true,
+			..Default::default()
+		},
+		PropertyDefinition {
+			name: b"address".to_vec(),
+			data_type: DataType::String,
+			..Default::default()
+		},
+		PropertyDefinition {
+			name: b"country_code".to_vec(),
+			data_type: DataType::Number,
+			description: b"ISO 3166-1 numeric country code".to_vec(),
+			..Default::default()
+		},
+		PropertyDefinition {
+			name: b"coordinates".to_vec(),
+			data_type: DataType::LatLong,
+			..Default::default()
+		},
+	]
+}
@@ -0,0 +1,52 @@
+//! Computes the IPFS CID of local files, so that off-chain references can be prepared offline.
+
+use std::{fs, io, path::Path};
+use sha2::{Digest, Sha256};
+use grid_runtime::grid_cid::Cid;
+
+// Files above that are split in several blocks by IPFS, under a CID that depends on its chunker
+const BLOCK_LIMIT: usize = 256 * 1024;
+
+/// Returns the CIDv1 (raw codec, sha2-256, base32) of a file, as given by `ipfs add --cid-version 1 --raw-leaves`
+/// for files that fit in a single block (256KiB). Larger files are rejected.
+pub fn file_cid<P: AsRef<Path>>(path: P) -> io::Result<String> {
+	let data = fs::read(path)?;
+	if data.len() > BLOCK_LIMIT {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"file larger than a single IPFS block (256KiB), add it to IPFS to get its CID"
+		));
+	}
+	Ok(data_cid(&data))
+}
+
+/// Returns the CIDv1 (raw codec, sha2-256, base32) of some data.
+pub fn data_cid(data: &[u8]) -> String {
+	let mut digest = [0u8; 32];
+	digest.copy_from_slice(&Sha256::digest(data));
+	String::from_utf8(Cid::raw_sha2_256(digest).to_base32()).expect("base32 is ASCII; qed")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn data_cid_matches_ipfs() {
+		// `echo -n "hello world" | ipfs add --cid-version 1 --raw-leaves`
+		assert_eq!(data_cid(b"hello world"), "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e");
+	}
+
+	#[test]
+	fn file_cid_up_to_a_single_block() {
+		let path = std::env::temp_dir().join(format!("grid-cid-test-{}", std::process::id()));
+
+		fs::write(&path, vec![0u8; BLOCK_LIMIT]).unwrap();
+		assert_eq!(file_cid(&path).unwrap(), data_cid(&vec![0u8; BLOCK_LIMIT]));
+
+		fs::write(&path, vec![0u8; BLOCK_LIMIT + 1]).unwrap();
+		let err = file_cid(&path).unwrap_err();
+		fs::remove_file(&path).unwrap();
+		assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+	}
+}
@@ -1,13 +1,45 @@
-use crate::service;
+use crate::{cid, service};
 use futures::{future, Future, sync::oneshot};
 use std::cell::RefCell;
 use tokio::runtime::Runtime;
 pub use substrate_cli::{VersionInfo, IntoExit, error};
-use substrate_cli::{informant, parse_and_execute, NoCustom};
+use substrate_cli::{informant, parse_and_execute, GetLogFilter, NoCustom};
 use substrate_service::{ServiceFactory, Roles as ServiceRoles};
 use crate::chain_spec;
 use std::ops::Deref;
+use std::path::PathBuf;
 use log::info;
+use structopt::StructOpt;
+
+/// Subcommands of the Grid node, besides the Substrate ones.
+#[derive(Debug, StructOpt, Clone)]
+pub enum CustomSubcommands {
+	/// Prints the IPFS CID (v1, raw, sha2-256) of a file up to 256KiB, to reference it off-chain
+	#[structopt(name = "cid")]
+	Cid(CidCmd),
+}
+
+impl GetLogFilter for CustomSubcommands {
+	fn get_log_filter(&self) -> Option<String> {
+		None
+	}
+}
+
+/// The `cid` command.
+#[derive(Debug, StructOpt, Clone)]
+pub struct CidCmd {
+	/// File to compute the CID of
+	#[structopt(parse(from_os_str))]
+	pub file: PathBuf,
+}
+
+impl CidCmd {
+	fn run(&self) -> error::Result<()> {
+		let cid = cid::file_cid(&self.file).map_err(|e| format!("{}: {}", self.file.display(), e))?;
+		println!("{}", cid);
+		Ok(())
+	}
+}
 
 /// Parse command line arguments into service configuration.
 pub fn run<I, T, E>(args: I, exit: E, version: VersionInfo) -> error::Result<()> where
@@ -15,7 +47,7 @@ pub fn run<I, T, E>(args: I, exit: E, version: VersionInfo) -> error::Result<()>
 	T: Into<std::ffi::OsString> + Clone,
 	E: IntoExit,
 {
-	parse_and_execute::<service::Factory, NoCustom, NoCustom, _, _, _, _, _>(
+	let cmd = parse_and_execute::<service::Factory, CustomSubcommands, NoCustom, _, _, _, _, _>(
 		load_spec, &version, "substrate-node", args, exit,
 	 	|exit, _custom_args, config| {
 			info!("{}", version.name);
@@ -39,7 +71,11 @@ pub fn run<I, T, E>(args: I, exit: E, version: VersionInfo) -> error::Result<()>
 				),
 			}.map_err(|e| format!("{:?}", e))
 		}
-	).map_err(Into::into).map(|_| ())
+	)?;
+	match cmd {
+		Some(CustomSubcommands::Cid(cmd)) => cmd.run(),
+		None => Ok(()),
+	}
 }
 
 fn load_spec(id: &str) -> Result<Option<chain_spec::ChainSpec>, String> {
@@ -6,6 +6,8 @@
 mod chain_spec;
 mod service;
 mod cli;
+mod rpc;
+mod cid;
 
 pub use substrate_cli::{VersionInfo, IntoExit, error};
 
@@ -0,0 +1,171 @@
+//! Grid JSON-RPC methods, served alongside the standard Substrate RPC.
+//! They proxy the Grid runtime APIs, so that clients don't have to decode raw storage.
+
+use std::{fmt::Debug, io, net::SocketAddr, sync::Arc};
+use jsonrpc_core::{Error, ErrorCode, IoHandler, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, RestApi, Server, ServerBuilder};
+use primitives::{Blake2Hasher, Bytes};
+use runtime_primitives::generic::BlockId;
+use substrate_client::{self as client, runtime_api::ProvideRuntimeApi};
+use grid_runtime::{AccountId, opaque::Block};
+use grid_runtime::grid_api::{GridPikeApi, GridSchemaApi};
+use grid_runtime::grid_pike::{Agent, Organization, Role};
+use grid_runtime::grid_schema::{PropertyDefinition, PropertyValue, Schema};
+
+/// Grid RPC methods.
+#[rpc]
+pub trait GridApi {
+	/// Returns an organization by ID.
+	#[rpc(name = "grid_org")]
+	fn org(&self, id: Bytes) -> Result<Option<Organization>>;
+
+	/// Returns the agent acting for an account within an organization.
+	#[rpc(name = "grid_agent")]
+	fn agent(&self, account: AccountId, org_id: Bytes) -> Result<Option<Agent<AccountId>>>;
+
+	/// Returns the agents acting for an account, one per organization.
+	#[rpc(name = "grid_agentsOfAccount")]
+	fn agents_of_account(&self, account: AccountId) -> Result<Vec<Agent<AccountId>>>;
+
+	/// Returns every agent of an organization.
+	#[rpc(name = "grid_agentsOfOrg")]
+	fn agents_of_org(&self, id: Bytes) -> Result<Vec<Agent<AccountId>>>;
+
+	/// Returns the roles held by the agent of an account within an organization.
+	#[rpc(name = "grid_rolesOfAgent")]
+	fn roles_of_agent(&self, account: AccountId, org_id: Bytes) -> Result<Vec<Role>>;
+
+	/// Checks whether an account is an active admin of an organization.
+	#[rpc(name = "grid_isAdmin")]
+	fn is_admin(&self, account: AccountId, org_id: Bytes) -> Result<bool>;
+
+	/// Checks whether an account holds a permission within an organization.
+	#[rpc(name = "grid_hasPermission")]
+	fn has_permission(&self, account: AccountId, org_id: Bytes, permission: Bytes) -> Result<bool>;
+
+	/// Returns a schema along with the property definitions of its current version.
+	#[rpc(name = "grid_schema")]
+	fn schema(&self, name: Bytes) -> Result<Option<(Schema, Vec<PropertyDefinition>)>>;
+
+	/// Returns the schemas owned by an organization.
+	#[rpc(name = "grid_schemasOfOrg")]
+	fn schemas_of_org(&self, org_id: Bytes) -> Result<Vec<Schema>>;
+
+	/// Validates property values against the current version of a schema, without submitting anything.
+	/// Returns the validation error, if any.
+	#[rpc(name = "grid_validateValues")]
+	fn validate_values(&self, schema: Bytes, values: Vec<PropertyValue>) -> Result<Option<String>>;
+}
+
+/// Grid RPC methods implementation, querying the best block.
+pub struct Grid<B, E, RA> {
+	client: Arc<client::Client<B, E, Block, RA>>,
+}
+
+impl<B, E, RA> Grid<B, E, RA> {
+	/// Creates new Grid RPC handler.
+	pub fn new(client: Arc<client::Client<B, E, Block, RA>>) -> Self {
+		Grid { client }
+	}
+}
+
+fn api_error(e: impl Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(1),
+		message: "Grid runtime API call failed".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<B, E, RA> Grid<B, E, RA> where
+	B: client::backend::Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+	E: client::CallExecutor<Block, Blake2Hasher> + Send + Sync + 'static,
+	RA: Send + Sync + 'static,
+{
+	fn best_block(&self) -> Result<BlockId<Block>> {
+		let info = self.client.info().map_err(api_error)?;
+		Ok(BlockId::hash(info.chain.best_hash))
+	}
+}
+
+impl<B, E, RA> GridApi for Grid<B, E, RA> where
+	B: client::backend::Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+	E: client::CallExecutor<Block, Blake2Hasher> + Send + Sync + 'static,
+	RA: Send + Sync + 'static,
+	client::Client<B, E, Block, RA>: ProvideRuntimeApi,
+	<client::Client<B, E, Block, RA> as ProvideRuntimeApi>::Api: GridPikeApi<Block> + GridSchemaApi<Block>,
+{
+	fn org(&self, id: Bytes) -> Result<Option<Organization>> {
+		let at = self.best_block()?;
+		self.client.runtime_api().org(&at, id.0).map_err(api_error)
+	}
+
+	fn agent(&self, account: AccountId, org_id: Bytes) -> Result<Option<Agent<AccountId>>> {
+		let at = self.best_block()?;
+		self.client.runtime_api().agent(&at, account, org_id.0).map_err(api_error)
+	}
+
+	fn agents_of_account(&self, account: AccountId) -> Result<Vec<Agent<AccountId>>> {
+		let at = self.best_block()?;
+		self.client.runtime_api().agents_of_account(&at, account).map_err(api_error)
+	}
+
+	fn agents_of_org(&self, id: Bytes) -> Result<Vec<Agent<AccountId>>> {
+		let at = self.best_block()?;
+		self.client.runtime_api().agents_of_org(&at, id.0).map_err(api_error)
+	}
+
+	fn roles_of_agent(&self, account: AccountId, org_id: Bytes) -> Result<Vec<Role>> {
+		let at = self.best_block()?;
+		self.client.runtime_api().roles_of_agent(&at, account, org_id.0).map_err(api_error)
+	}
+
+	fn is_admin(&self, account: AccountId, org_id: Bytes) -> Result<bool> {
+		let at = self.best_block()?;
+		self.client.runtime_api().is_admin(&at, account, org_id.0).map_err(api_error)
+	}
+
+	fn has_permission(&self, account: AccountId, org_id: Bytes, permission: Bytes) -> Result<bool> {
+		let at = self.best_block()?;
+		self.client.runtime_api().has_permission(&at, account, org_id.0, permission.0).map_err(api_error)
+	}
+
+	fn schema(&self, name: Bytes) -> Result<Option<(Schema, Vec<PropertyDefinition>)>> {
+		let at = self.best_block()?;
+		self.client.runtime_api().schema(&at, name.0).map_err(api_error)
+	}
+
+	fn schemas_of_org(&self, org_id: Bytes) -> Result<Vec<Schema>> {
+		let at = self.best_block()?;
+		self.client.runtime_api().schemas_of_org(&at, org_id.0).map_err(api_error)
+	}
+
+	fn validate_values(&self, schema: Bytes, values: Vec<PropertyValue>) -> Result<Option<String>> {
+		let at = self.best_block()?;
+		let result = self.client.runtime_api().validate_values(&at, schema.0, values).map_err(api_error)?;
+		Ok(result.err().map(|e| String::from_utf8_lossy(&e).into_owned()))
+	}
+}
+
+/// Starts the Grid RPC HTTP server, accepting requests from the given origins (any if None).
+/// The returned server stops as soon as it is dropped.
+pub fn start_http<B, E, RA>(
+	addr: &SocketAddr,
+	cors: Option<&Vec<String>>,
+	client: Arc<client::Client<B, E, Block, RA>>,
+) -> io::Result<Server> where
+	Grid<B, E, RA>: GridApi,
+{
+	let mut io = IoHandler::new();
+	io.extend_with(Grid::new(client).to_delegate());
+	// Same CORS policy as the standard RPC server
+	let allowed_origins: DomainsValidation<AccessControlAllowOrigin> = cors
+		.map(|origins| origins.iter().map(|o| AccessControlAllowOrigin::from(o.as_str())).collect::<Vec<_>>())
+		.into();
+	ServerBuilder::new(io)
+		.threads(1)
+		.rest_api(if cors.is_some() { RestApi::Secure } else { RestApi::Unsecure })
+		.cors(allowed_origins)
+		.start_http(addr)
+}
@@ -2,7 +2,7 @@
 
 #![warn(unused_extern_crates)]
 
-use std::sync::Arc;
+use std::{sync::Arc, thread};
 use log::info;
 use transaction_pool::{self, txpool::{Pool as TransactionPool}};
 use grid_runtime::{self, GenesisConfig, opaque::Block, RuntimeApi};
@@ -19,6 +19,7 @@ use inherents::InherentDataProviders;
 use network::construct_simple_protocol;
 use substrate_executor::native_executor_instance;
 use substrate_service::construct_service_factory;
+use crate::rpc;
 
 pub use substrate_executor::NativeExecutor;
 // Our native executor instance.
@@ -52,9 +53,28 @@ construct_service_factory! {
 		Genesis = GenesisConfig,
 		Configuration = NodeConfig,
 		FullService = FullComponents<Self>
-			{ |config: FactoryFullConfiguration<Self>, executor: TaskExecutor|
-				FullComponents::<Factory>::new(config, executor)
-			},
+			{ |config: FactoryFullConfiguration<Self>, executor: TaskExecutor| {
+				// Grid RPC is served next to the standard RPC, on the following port of the same interface
+				// (i.e. public with `--rpc-external`), and accepts the same origins (`--rpc-cors`)
+				let grid_rpc_addr = match config.rpc_http {
+					Some(mut addr) => {
+						let port = addr.port().checked_add(1)
+							.ok_or_else(|| format!("No port left for the Grid RPC server after {}", addr))?;
+						addr.set_port(port);
+						Some(addr)
+					},
+					None => None,
+				};
+				let grid_rpc_cors = config.rpc_cors.clone();
+				let service = FullComponents::<Factory>::new(config, executor)?;
+				if let Some(addr) = grid_rpc_addr {
+					let server = rpc::start_http(&addr, grid_rpc_cors.as_ref(), service.client())
+						.map_err(|e| format!("Unable to start Grid RPC server on {}: {}", addr, e))?;
+					info!("Grid RPC server listening on {}", addr);
+					thread::spawn(move || server.wait());
+				}
+				Ok(service)
+			} },
 		AuthoritySetup = {
 			|service: Self::FullService, executor: TaskExecutor, key: Option<Arc<Pair>>| {
 				if let Some(key) = key {